@@ -0,0 +1,141 @@
+use crate::{translation_params, Error, ErrorKind, Result, TranslatableTextList, TranslatedText, TranslationOptions};
+
+/// Maximum number of texts DeepL accepts in one translation request.
+pub(crate) const MAX_TEXTS_PER_REQUEST: usize = 50;
+
+/// Maximum size of a translation request body in bytes.
+pub(crate) const MAX_REQUEST_SIZE: usize = 128 * 1024;
+
+/// Private helper that returns the size of a form parameter once it is URL-encoded, including the `&` separator.
+fn encoded_len(name: &str, value: &str) -> usize {
+    let value_len: usize = value
+        .bytes()
+        .map(|b| match b {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'*' | b'-' | b'.' | b'_' | b' ' => 1,
+            _ => 3,
+        })
+        .sum();
+    name.len() + 1 + value_len + 1
+}
+
+/// Private helper that splits a text list into batches that stay within the limits of DeepL for a
+/// single request. The order of the texts is kept. A single text that exceeds the size limit on its
+/// own ends up in a batch of its own and will be rejected by the server.
+pub(crate) fn split_text_list(
+    options: &Option<TranslationOptions>,
+    text_list: TranslatableTextList,
+) -> Result<Vec<TranslatableTextList>> {
+    let empty_list = || TranslatableTextList {
+        source_language: text_list.source_language.clone(),
+        target_language: text_list.target_language.clone(),
+        texts: vec![],
+    };
+    let base_size: usize = translation_params(options.clone(), empty_list())?
+        .iter()
+        .map(|(name, value)| encoded_len(name, value))
+        .sum();
+
+    let mut batches = vec![empty_list()];
+    let mut size = base_size;
+    for text in &text_list.texts {
+        let text_size = encoded_len("text", text);
+        let current = batches.last().unwrap();
+        if !current.texts.is_empty()
+            && (current.texts.len() == MAX_TEXTS_PER_REQUEST || size + text_size > MAX_REQUEST_SIZE)
+        {
+            batches.push(empty_list());
+            size = base_size;
+        }
+        batches.last_mut().unwrap().texts.push(text.clone());
+        size += text_size;
+    }
+    Ok(batches)
+}
+
+/// Private result of translating the batches of a text list. Holds the translation of each text in
+/// the original order, which is missing if its batch failed or was not sent, and the first error.
+/// This way, the translations of the successful batches, which are billed nevertheless, can still be
+/// cached if another batch fails.
+#[derive(Default)]
+pub(crate) struct BatchTranslations {
+    pub(crate) translations: Vec<Option<TranslatedText>>,
+    pub(crate) error: Option<Error>,
+}
+
+impl BatchTranslations {
+    /// Add the result of the next batch, which consists of `size` texts.
+    pub(crate) fn push(&mut self, size: usize, result: Result<Vec<TranslatedText>>) {
+        match result {
+            Ok(translations) if translations.len() == size => {
+                self.translations.extend(translations.into_iter().map(Some));
+            }
+            result => {
+                self.translations.extend((0..size).map(|_| None));
+                let error = result.err().unwrap_or_else(|| ErrorKind::DeserializationError.into());
+                self.error.get_or_insert(error);
+            }
+        }
+    }
+
+    /// The translations of all texts, or the first error.
+    pub(crate) fn into_result(self) -> Result<Vec<TranslatedText>> {
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(self.translations.into_iter().flatten().collect()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::text_list;
+
+    fn strings(texts: Vec<String>) -> TranslatableTextList {
+        TranslatableTextList { texts, ..text_list(&[]) }
+    }
+
+    fn batch_sizes(batches: &[TranslatableTextList]) -> Vec<usize> {
+        batches.iter().map(|batch| batch.texts.len()).collect()
+    }
+
+    #[test]
+    fn encoded_len() {
+        assert_eq!(super::encoded_len("text", "ja nein"), 13);
+        assert_eq!(super::encoded_len("text", "ä&"), 15);
+    }
+
+    #[test]
+    fn split_by_count() {
+        let texts: Vec<String> = (0..120).map(|i| i.to_string()).collect();
+        let batches = split_text_list(&None, strings(texts.clone())).unwrap();
+        assert_eq!(batch_sizes(&batches), vec![50, 50, 20]);
+        assert_eq!(batches.iter().flat_map(|batch| batch.texts.clone()).collect::<Vec<_>>(), texts);
+        assert!(batches.iter().all(|batch| batch.target_language.as_str() == "EN-US"));
+    }
+
+    #[test]
+    fn split_by_size() {
+        let texts = vec!["a".repeat(50 * 1024), "b".repeat(50 * 1024), "c".repeat(50 * 1024)];
+        assert_eq!(batch_sizes(&split_text_list(&None, strings(texts)).unwrap()), vec![2, 1]);
+
+        // Non-ASCII characters take up more space once encoded.
+        let texts = vec!["ä".repeat(10 * 1024), "ö".repeat(10 * 1024), "ü".repeat(10 * 1024)];
+        assert_eq!(batch_sizes(&split_text_list(&None, strings(texts)).unwrap()), vec![2, 1]);
+
+        let options = Some(TranslationOptions {
+            context: Some("x".repeat(30 * 1024)),
+            ..Default::default()
+        });
+        let texts = vec!["a".repeat(50 * 1024), "b".repeat(50 * 1024)];
+        assert_eq!(batch_sizes(&split_text_list(&options, strings(texts)).unwrap()), vec![1, 1]);
+
+        let texts = vec!["a".repeat(200 * 1024), "b".to_string()];
+        assert_eq!(batch_sizes(&split_text_list(&None, strings(texts)).unwrap()), vec![1, 1]);
+    }
+
+    #[test]
+    fn split_empty() {
+        assert_eq!(batch_sizes(&split_text_list(&None, text_list(&[])).unwrap()), vec![0]);
+    }
+}
@@ -14,6 +14,9 @@
 //! export DEEPL_API_KEY=YOUR_KEY
 //! ```
 //!
+//! To use a different server than the official DeepL API endpoints, e. g. a local stand-in or a
+//! proxy, set its URL in the `DEEPL_SERVER_URL` environment variable.
+//!
 //! # Examples
 //!
 //! ## Overview
@@ -55,14 +58,79 @@
 //! By providing the options `--input-file` and / or `--output-file`, you can tell `deepl` to
 //! read from / write to files, rather than `STDIN` / `STDOUT`.
 //!
+//! Large inputs are split at paragraph and sentence boundaries and translated in several chunks,
+//! the line breaks between them are kept as they are.
+//!
+//! To avoid running out of quota unexpectedly, `--max-characters` refuses translations that would
+//! push the character usage of the current billing period beyond the given number.
+//!
+//! ```text
+//! shell> echo "Please go home." | deepl translate --target-language DE --max-characters 3322
+//! Error: The translation of 16 characters would exceed the character budget, 5 characters remaining.
+//! ```
+//!
+//! ## Estimating Costs
+//!
+//! Before translating large amounts of text, `--dry-run` reports how many characters will be billed
+//! and whether they fit into the remaining quota, without translating anything.
+//!
+//! ```text
+//! shell> echo "Please go home." | deepl translate --target-language DE --dry-run
+//! Billable characters: 16
+//! Characters remaining in the current billing period: 246683
+//! Characters remaining after the translation: 246667
+//! ```
+//!
+//! `deepl estimate` does the same for a set of text files and target languages, each of which is
+//! billed separately.
+//!
+//! ```text
+//! shell> deepl estimate --target-language DE,FR README.md CHANGELOG.md
+//! Billable characters: 21304
+//! Characters remaining in the current billing period: 246683
+//! Characters remaining after the translation: 225379
+//! ```
+//!
+//! ## Translating Documents
+//!
+//! ```text
+//! shell> deepl document --target-language DE --input-file manual.docx --output-file manual_de.docx
+//! Uploaded document, resume with: --document-id 04DE5AD98A02647D83285A36021911C6 --document-key 0CB0054F1C132C1625B392EADDA41CB754A742822F6877173029A6C487E7F60A
+//! Translating, about 3 seconds remaining...
+//! Done, 1532 characters billed.
+//! ```
+//!
+//! Progress information is printed to `STDERR`. Should `deepl` be interrupted, the translation can
+//! be resumed by passing the printed `--document-id` and `--document-key` instead of `--input-file`.
+//!
+//! ## Caching Translations
+//!
+//! With `--cache-dir`, `deepl translate` stores its translations in the given directory and reuses
+//! them for identical requests, which then are not billed again. Entries expire after 30 days, and
+//! the oldest entries are removed once the cache exceeds 100 MiB.
+//!
+//! ```text
+//! shell> echo "Please go home." | deepl translate --target-language DE --cache-dir .deepl-cache
+//! Bitte gehen Sie nach Hause.
+//! shell> deepl cache stats --cache-dir .deepl-cache
+//! Cached translations: 1
+//! Cache size in bytes: 96
+//! shell> deepl cache clear --cache-dir .deepl-cache
+//! Removed 1 cached translations.
+//! ```
+//!
 //! ## Retrieving Account Usage & Limits
 //!
 //! ```text
 //! shell> deepl usage-information
 //! Available characters per billing period: 250000
 //! Characters already translated in the current billing period: 3317
+//! Characters remaining in the current billing period: 246683 (1.3% used)
 //! ```
 //!
+//! Depending on the account type, also document and API key counters and the current billing
+//! period are shown.
+//!
 //! ## Retrieving Available Languages
 //!
 //! ```text
@@ -74,12 +142,27 @@
 //!   ...
 //!
 //! DeepL can translate to the following target languages:
-//!   DE    (German)
-//!   EN-GB (English (British))
-//!   EN-US (English (American))
-//!   ES    (Spanish)
+//!   DE    formality (German)
+//!   EN-GB           (English (British))
+//!   EN-US           (English (American))
+//!   ES    formality (Spanish)
 //!   ...
 //! ```
+//!
+//! Target languages which support the formality options are marked. Use `--supports-formality`
+//! to only list these.
+//!
+//! # Exit Codes
+//!
+//! | Code | Meaning                                                  |
+//! |------|----------------------------------------------------------|
+//! | 0    | Success                                                  |
+//! | 1    | General error, e. g. invalid API key or unreadable file  |
+//! | 2    | Invalid commandline arguments                            |
+//! | 3    | The translation quota of the account has been exceeded   |
+//! | 4    | Too many requests, even after retrying                   |
+//! | 5    | The request is too large for DeepL                       |
+//! | 6    | The translation would exceed `--max-characters`          |
 
 use deepl_api::*;
 use std::fs;
@@ -91,34 +174,83 @@ use parse_arguments::*;
 fn main() {
     let opts: Opts = Opts::parse();
 
+    // Managing the cache does not require an API key.
+    if let SubCmd::Cache(c) = &opts.subcmd {
+        if let Err(e) = cache(c) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     let key = match std::env::var("DEEPL_API_KEY") {
-        Ok(val) if val.len() > 0 => val,
+        Ok(val) if !val.is_empty() => val,
         _ => {
             eprintln!("Error: no DEEPL_API_KEY found. Please provide your API key in this environment variable.");
             std::process::exit(1);
         }
     };
 
-    let deepl = DeepL::new(key);
+    let mut builder = DeepL::builder(key).retry_policy(RetryPolicy::default());
+    if let Ok(server_url) = std::env::var("DEEPL_SERVER_URL") {
+        if !server_url.is_empty() {
+            builder = builder.server_url(&server_url);
+        }
+    }
+    if let SubCmd::Translate(t) = &opts.subcmd {
+        if let Some(cache_dir) = &t.cache_dir {
+            builder = builder.cache(TranslationCache::new(cache_dir));
+        }
+        if let Some(max_characters) = t.max_characters {
+            builder = builder.budget(CharacterBudget {
+                max_characters: Some(max_characters),
+                ..Default::default()
+            });
+        }
+    }
+    let deepl = match builder.build() {
+        Ok(deepl) => deepl,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
 
     let result = match opts.subcmd {
         SubCmd::Translate(t) => translate(&deepl, &t),
+        SubCmd::Document(d) => document(&deepl, &d),
+        SubCmd::Estimate(e) => estimate(&deepl, &e),
         SubCmd::UsageInformation => usage_information(&deepl),
-        SubCmd::Languages => languages(&deepl),
+        SubCmd::Languages(l) => languages(&deepl, &l),
+        SubCmd::Cache(_) => unreachable!("the cache is managed before the API key is read"),
     };
 
     if let Err(e) = result {
         eprintln!("Error: {}", e);
-        std::process::exit(1)
+        for cause in e.iter().skip(1) {
+            eprintln!("Caused by: {}", cause);
+        }
+        std::process::exit(exit_code(&e))
+    }
+}
+
+/// Maps errors to the exit codes documented in [Exit Codes](self#exit-codes).
+fn exit_code(e: &Error) -> i32 {
+    match e.kind() {
+        ErrorKind::QuotaExceeded(..) => 3,
+        ErrorKind::RateLimited(..) => 4,
+        ErrorKind::PayloadTooLarge(..) => 5,
+        ErrorKind::BudgetExceeded(..) => 6,
+        _ => 1,
     }
 }
 
 fn translate(deepl: &DeepL, t: &Translate) -> Result<()> {
     let mut t_opts = TranslationOptions {
-        split_sentences: None,
-        preserve_formatting: None,
-        formality: None,
-        glossary_id: None
+        splitting_tags: t.splitting_tags.clone(),
+        non_splitting_tags: t.non_splitting_tags.clone(),
+        ignore_tags: t.ignore_tags.clone(),
+        ..Default::default()
     };
     if t.preserve_formatting {
         t_opts.preserve_formatting = Some(true);
@@ -129,6 +261,35 @@ fn translate(deepl: &DeepL, t: &Translate) -> Result<()> {
     if t.formality_more {
         t_opts.formality = Some(Formality::More);
     }
+    if t.formality_prefer_less {
+        t_opts.formality = Some(Formality::PreferLess);
+    }
+    if t.formality_prefer_more {
+        t_opts.formality = Some(Formality::PreferMore);
+    }
+    match t.tag_handling.as_deref() {
+        Some("xml") => t_opts.tag_handling = Some(TagHandling::Xml),
+        Some("html") => t_opts.tag_handling = Some(TagHandling::Html),
+        _ => {}
+    }
+    if t.no_outline_detection {
+        t_opts.outline_detection = Some(false);
+    }
+    if let Some(context) = t.context.clone() {
+        t_opts.context = Some(context);
+    }
+    if let Some(filepath) = t.context_file.clone() {
+        t_opts.context = Some(fs::read_to_string(filepath)?);
+    }
+    match t.model_type.as_deref() {
+        Some("quality_optimized") => t_opts.model_type = Some(ModelType::QualityOptimized),
+        Some("prefer_quality_optimized") => t_opts.model_type = Some(ModelType::PreferQualityOptimized),
+        Some("latency_optimized") => t_opts.model_type = Some(ModelType::LatencyOptimized),
+        _ => {}
+    }
+    if t.show_billed_characters {
+        t_opts.show_billed_characters = Some(true);
+    }
 
     let mut text = String::new();
     if let Some(filepath) = t.input_file.clone() {
@@ -138,15 +299,25 @@ fn translate(deepl: &DeepL, t: &Translate) -> Result<()> {
     }
 
     let texts = TranslatableTextList {
-        source_language: t.source_language.clone(),
-        target_language: t.target_language.clone(),
+        source_language: t.source_language.as_deref().map(str::parse).transpose()?,
+        target_language: t.target_language.parse()?,
         texts: vec![text],
     };
 
-    let translations = deepl.translate(Some(t_opts), texts)?;
+    if t.dry_run {
+        print_estimate(&deepl.estimate(&texts)?);
+        return Ok(());
+    }
+
+    let translations = deepl.translate_long_texts(Some(t_opts), texts)?;
     let mut output = String::new();
-    for t in translations {
-        output.push_str(&t.text);
+    let mut billed_characters = 0;
+    for translation in translations {
+        output.push_str(&translation.text);
+        billed_characters += translation.billed_characters.unwrap_or_default();
+    }
+    if t.show_billed_characters {
+        eprintln!("Billed characters: {}", billed_characters);
     }
 
     if let Some(filepath) = t.output_file.clone() {
@@ -158,6 +329,89 @@ fn translate(deepl: &DeepL, t: &Translate) -> Result<()> {
     Ok(())
 }
 
+fn estimate(deepl: &DeepL, e: &Estimate) -> Result<()> {
+    let target_languages = e.target_language.iter().map(|language| language.parse()).collect::<Result<Vec<Language>>>()?;
+    print_estimate(&deepl.estimate_files(&e.input_files, &target_languages)?);
+    Ok(())
+}
+
+fn print_estimate(estimate: &CostEstimate) {
+    println!("Billable characters: {}", estimate.characters);
+    println!(
+        "Characters remaining in the current billing period: {}",
+        estimate.remaining_characters
+    );
+    if estimate.fits_quota() {
+        println!(
+            "Characters remaining after the translation: {}",
+            estimate.remaining_characters_after()
+        );
+    } else {
+        println!("The translation exceeds the remaining characters of the current billing period.");
+    }
+}
+
+fn document(deepl: &DeepL, d: &Document) -> Result<()> {
+    let handle = match (&d.document_id, &d.document_key) {
+        (Some(document_id), Some(document_key)) => DocumentHandle {
+            document_id: document_id.clone(),
+            document_key: document_key.clone(),
+        },
+        _ => {
+            let mut d_opts = DocumentTranslationOptions {
+                formality: None,
+                glossary_id: d.glossary_id.clone(),
+                output_format: d.output_format.clone(),
+            };
+            if d.formality_less {
+                d_opts.formality = Some(Formality::Less);
+            }
+            if d.formality_more {
+                d_opts.formality = Some(Formality::More);
+            }
+            if d.formality_prefer_less {
+                d_opts.formality = Some(Formality::PreferLess);
+            }
+            if d.formality_prefer_more {
+                d_opts.formality = Some(Formality::PreferMore);
+            }
+
+            // Both arguments are required by clap if no document id was given.
+            let document = TranslatableDocument::from_file(
+                d.input_file.clone().unwrap_or_default(),
+                d.source_language.as_deref().map(str::parse).transpose()?,
+                d.target_language.as_deref().unwrap_or_default().parse()?,
+            )?;
+            let handle = deepl.upload_document(Some(d_opts), document)?;
+            eprintln!(
+                "Uploaded document, resume with: --document-id {} --document-key {}",
+                handle.document_id, handle.document_key
+            );
+            handle
+        }
+    };
+
+    let status = loop {
+        let status = deepl.document_status(&handle)?;
+        if status.is_finished() {
+            break status.check()?;
+        }
+        match (status.status, status.seconds_remaining) {
+            (DocumentState::Queued, _) => eprintln!("Waiting for translation to start..."),
+            (_, Some(seconds)) => eprintln!("Translating, about {} seconds remaining...", seconds),
+            (_, None) => eprintln!("Translating..."),
+        }
+        std::thread::sleep(status.poll_interval());
+    };
+
+    fs::write(&d.output_file, deepl.download_document(&handle)?)?;
+    if let Some(billed_characters) = status.billed_characters {
+        eprintln!("Done, {} characters billed.", billed_characters);
+    }
+
+    Ok(())
+}
+
 fn usage_information(deepl: &DeepL) -> Result<()> {
     let usage = deepl.usage_information()?;
     println!(
@@ -168,12 +422,41 @@ fn usage_information(deepl: &DeepL) -> Result<()> {
         "Characters already translated in the current billing period: {}",
         usage.character_count
     );
+    println!(
+        "Characters remaining in the current billing period: {} ({:.1}% used)",
+        usage.remaining_characters(),
+        usage.percent_used()
+    );
+    if let (Some(start_time), Some(end_time)) = (usage.start_time, usage.end_time) {
+        println!("Current billing period: {} - {}", start_time, end_time);
+    }
+    if let Some(document_limit) = usage.document_limit {
+        println!("Available documents per billing period: {}", document_limit);
+    }
+    if let Some(document_count) = usage.document_count {
+        println!("Documents already translated in the current billing period: {}", document_count);
+    }
+    if let Some(team_document_limit) = usage.team_document_limit {
+        println!("Available team documents per billing period: {}", team_document_limit);
+    }
+    if let Some(team_document_count) = usage.team_document_count {
+        println!("Team documents already translated in the current billing period: {}", team_document_count);
+    }
+    if let Some(api_key_character_limit) = usage.api_key_character_limit {
+        println!("Available characters for this API key per billing period: {}", api_key_character_limit);
+    }
+    if let Some(api_key_character_count) = usage.api_key_character_count {
+        println!("Characters already translated with this API key in the current billing period: {}", api_key_character_count);
+    }
     Ok(())
 }
 
-fn languages(deepl: &DeepL) -> Result<()> {
+fn languages(deepl: &DeepL, l: &Languages) -> Result<()> {
     let source_langs = deepl.source_languages()?;
-    let target_langs = deepl.target_languages()?;
+    let mut target_langs = deepl.target_languages()?;
+    if l.supports_formality {
+        target_langs.retain(|lang| lang.supports_formality == Some(true));
+    }
     println!("DeepL can translate from the following source languages:");
     for lang in source_langs {
         println!("  {:<5} ({})", lang.language, lang.name)
@@ -181,7 +464,26 @@ fn languages(deepl: &DeepL) -> Result<()> {
     println!();
     println!("DeepL can translate to the following target languages:");
     for lang in target_langs {
-        println!("  {:<5} ({})", lang.language, lang.name)
+        let formality = match lang.supports_formality {
+            Some(true) => "formality",
+            _ => "",
+        };
+        println!("  {:<5} {:<9} ({})", lang.language, formality, lang.name)
+    }
+    Ok(())
+}
+
+fn cache(c: &Cache) -> Result<()> {
+    match &c.action {
+        CacheAction::Stats(d) => {
+            let stats = TranslationCache::new(&d.cache_dir).stats()?;
+            println!("Cached translations: {}", stats.entries);
+            println!("Cache size in bytes: {}", stats.size);
+        }
+        CacheAction::Clear(d) => {
+            let removed = TranslationCache::new(&d.cache_dir).clear()?;
+            println!("Removed {} cached translations.", removed);
+        }
     }
     Ok(())
 }
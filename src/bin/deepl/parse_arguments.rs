@@ -1,5 +1,4 @@
 pub use clap::Parser;
-pub use clap::Subcommand;
 
 /// Command line client for the DeepL API.
 #[derive(Parser, Debug)]
@@ -12,10 +11,13 @@ pub struct Opts {
 #[derive(clap::Subcommand, Debug)]
 pub enum SubCmd {
     Translate(Translate),
+    Document(Document),
+    Estimate(Estimate),
     /// Fetch information about account usage & limits.
     UsageInformation,
     /// Fetch list of available source and target languages.
-    Languages,
+    Languages(Languages),
+    Cache(Cache),
 }
 
 /// A subcommand for controlling testing
@@ -38,9 +40,139 @@ pub struct Translate {
     #[clap(long)]
     pub preserve_formatting: bool,
     /// Increase formality
-    #[clap(long)]
+    #[clap(long, group = "formality")]
     pub formality_more: bool,
     /// Decrease formality
+    #[clap(long, group = "formality")]
+    pub formality_less: bool,
+    /// Increase formality, if supported by the target language
+    #[clap(long, group = "formality")]
+    pub formality_prefer_more: bool,
+    /// Decrease formality, if supported by the target language
+    #[clap(long, group = "formality")]
+    pub formality_prefer_less: bool,
+
+    /// Handle markup tags in the input
+    #[clap(long, value_parser = ["xml", "html"])]
+    pub tag_handling: Option<String>,
+    /// Disable automatic detection of the XML structure
+    #[clap(long)]
+    pub no_outline_detection: bool,
+    /// Comma-separated list of XML tags which always cause splits
+    #[clap(long, value_delimiter = ',')]
+    pub splitting_tags: Option<Vec<String>>,
+    /// Comma-separated list of XML tags which never split sentences
+    #[clap(long, value_delimiter = ',')]
+    pub non_splitting_tags: Option<Vec<String>>,
+    /// Comma-separated list of XML tags whose content is not translated
+    #[clap(long, value_delimiter = ',')]
+    pub ignore_tags: Option<Vec<String>>,
+
+    /// Additional context for the translation, which is not translated itself
+    #[clap(long, conflicts_with = "context_file")]
+    pub context: Option<String>,
+    /// Read the additional context for the translation from this file
+    #[clap(long)]
+    pub context_file: Option<String>,
+
+    /// Kind of model to use for the translation
+    #[clap(long, value_parser = ["quality_optimized", "prefer_quality_optimized", "latency_optimized"])]
+    pub model_type: Option<String>,
+    /// Print the number of billed characters to STDERR
+    #[clap(long)]
+    pub show_billed_characters: bool,
+
+    /// Reuse translations cached in this directory and cache new ones
+    #[clap(long)]
+    pub cache_dir: Option<String>,
+    /// Refuse the translation if it would push the character usage of the billing period beyond this number
+    #[clap(long)]
+    pub max_characters: Option<u64>,
+    /// Only report the billable characters and the remaining quota, without translating
+    #[clap(long)]
+    pub dry_run: bool,
+}
+
+/// Estimate the billable characters of translating text files
+#[derive(Parser, Debug)]
+pub struct Estimate {
+    /// Comma-separated list of target languages (required)
+    #[clap(long, required = true, value_delimiter = ',')]
+    pub target_language: Vec<String>,
+    /// Input filepaths (required)
+    #[clap(required = true)]
+    pub input_files: Vec<String>,
+}
+
+/// Fetch list of available source and target languages
+#[derive(Parser, Debug)]
+pub struct Languages {
+    /// Only list target languages which support the formality options
+    #[clap(long)]
+    pub supports_formality: bool,
+}
+
+/// Translate a document (docx, pptx, xlsx, pdf, html, txt)
+#[derive(Parser, Debug)]
+pub struct Document {
+    /// Source language (optional)
+    #[clap(long)]
+    pub source_language: Option<String>,
+    /// Target language (required, unless resuming)
+    #[clap(long, required_unless_present = "document_id")]
+    pub target_language: Option<String>,
+    /// Input filepath (required, unless resuming)
+    #[clap(long, required_unless_present = "document_id", conflicts_with = "document_id")]
+    pub input_file: Option<String>,
+    /// Output filepath (required)
+    #[clap(long)]
+    pub output_file: String,
+    /// File extension of the desired output format, e. g. docx (optional)
     #[clap(long)]
+    pub output_format: Option<String>,
+
+    /// Use this glossary for the translation
+    #[clap(long)]
+    pub glossary_id: Option<String>,
+    /// Increase formality
+    #[clap(long, group = "formality")]
+    pub formality_more: bool,
+    /// Decrease formality
+    #[clap(long, group = "formality")]
     pub formality_less: bool,
+    /// Increase formality, if supported by the target language
+    #[clap(long, group = "formality")]
+    pub formality_prefer_more: bool,
+    /// Decrease formality, if supported by the target language
+    #[clap(long, group = "formality")]
+    pub formality_prefer_less: bool,
+
+    /// Resume a previously started translation with this document ID
+    #[clap(long, requires = "document_key")]
+    pub document_id: Option<String>,
+    /// Document key of the previously started translation
+    #[clap(long, requires = "document_id")]
+    pub document_key: Option<String>,
+}
+
+/// Inspect or clear the local translation cache
+#[derive(Parser, Debug)]
+pub struct Cache {
+    #[clap(subcommand)]
+    pub action: CacheAction,
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub enum CacheAction {
+    /// Show the number and size of the cached translations.
+    Stats(CacheDir),
+    /// Remove all cached translations.
+    Clear(CacheDir),
+}
+
+#[derive(Parser, Debug)]
+pub struct CacheDir {
+    /// Cache directory (required)
+    #[clap(long)]
+    pub cache_dir: String,
 }
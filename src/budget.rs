@@ -0,0 +1,155 @@
+use crate::{billable_characters, ErrorKind, Result, TranslatableTextList, UsageInformation};
+use error_chain::*;
+use std::sync::{Arc, Mutex};
+
+/// Limits for the character usage of the account, which are checked before each
+/// [translation](crate::DeepL::translate), see [DeepLBuilder::budget](crate::DeepLBuilder::budget).
+/// A translation that would push the usage of the current billing period past one of the limits
+/// fails with a [BudgetExceeded](ErrorKind::BudgetExceeded) error, without being sent to DeepL.
+///
+/// The usage is fetched via [usage_information](crate::DeepL::usage_information) before the first
+/// translation and tracked locally afterwards, so usage by other clients of the same account is only
+/// seen by new [DeepL](crate::DeepL) instances. After a failed translation, the usage is fetched
+/// again, as parts of it may have been billed.
+///
+/// # Example
+///
+/// ```rust
+/// use deepl_api::*;
+///
+/// let deepl = DeepL::builder("YOUR_KEY".to_string())
+///     .budget(CharacterBudget {
+///         max_percent_used: Some(90.0),
+///         ..Default::default()
+///     })
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CharacterBudget {
+    /// Maximum number of characters that may be used in the billing period.
+    pub max_characters: Option<u64>,
+    /// Maximum share of the character limit of the account that may be used, in percent.
+    pub max_percent_used: Option<f64>,
+}
+
+/// Private guard that enforces a [CharacterBudget], shared by all requests of a client.
+#[derive(Clone, Debug)]
+pub(crate) struct BudgetGuard {
+    budget: CharacterBudget,
+    /// The allowed number of characters and the characters used so far, once known.
+    usage: Arc<Mutex<Option<(u64, u64)>>>,
+}
+
+impl BudgetGuard {
+    pub(crate) fn new(budget: CharacterBudget) -> BudgetGuard {
+        BudgetGuard {
+            budget,
+            usage: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Start tracking from the given usage information.
+    pub(crate) fn set_usage(&self, usage_information: &UsageInformation) {
+        let mut allowed = u64::MAX;
+        if let Some(max_characters) = self.budget.max_characters {
+            allowed = allowed.min(max_characters);
+        }
+        if let Some(max_percent_used) = self.budget.max_percent_used {
+            allowed = allowed.min((usage_information.character_limit as f64 * max_percent_used / 100.0) as u64);
+        }
+        self.usage.lock().unwrap().get_or_insert((allowed, usage_information.character_count));
+    }
+
+    /// Count the characters of a text list as used, or fail if they exceed the budget. Returns
+    /// `false` without reserving anything if the usage is not known, e. g. because another translation
+    /// failed in the meantime; it then has to be [set](BudgetGuard::set_usage) before trying again.
+    pub(crate) fn reserve(&self, text_list: &TranslatableTextList) -> Result<bool> {
+        let characters = billable_characters(text_list);
+        let mut usage = self.usage.lock().unwrap();
+        let Some((allowed, used)) = usage.as_mut() else {
+            return Ok(false);
+        };
+        let remaining = allowed.saturating_sub(*used);
+        if characters > remaining {
+            bail!(ErrorKind::BudgetExceeded(characters, remaining));
+        }
+        *used += characters;
+        Ok(true)
+    }
+
+    /// Forget the tracked usage, so that it is fetched again before the next translation.
+    pub(crate) fn reset(&self) {
+        *self.usage.lock().unwrap() = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{text_list, MockServer};
+
+    #[test]
+    fn budget() {
+        let server = MockServer::start();
+        server.set_character_limit(100);
+        let deepl = server.builder()
+            .budget(CharacterBudget { max_percent_used: Some(50.0), ..Default::default() })
+            .build()
+            .unwrap();
+        deepl.translate(None, text_list(&[&"ja ".repeat(10)])).unwrap();
+        let requests = server.request_count();
+        let error = deepl.translate(None, text_list(&[&"ja ".repeat(10)])).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::BudgetExceeded(30, 20)));
+        assert_eq!(server.request_count(), requests);
+        deepl.translate(None, text_list(&[&"ja ".repeat(6)])).unwrap();
+
+        // A new client starts from the current usage of the account.
+        server.deepl().translate(None, text_list(&["ja"])).unwrap();
+        let deepl = server.builder()
+            .budget(CharacterBudget { max_characters: Some(50), max_percent_used: Some(80.0) })
+            .build()
+            .unwrap();
+        let error = deepl.translate(None, text_list(&["ja"])).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::BudgetExceeded(2, 0)));
+    }
+
+    #[test]
+    fn failed_batch() {
+        let server = MockServer::start();
+        server.set_character_limit(100);
+        let deepl = server.builder()
+            .budget(CharacterBudget { max_characters: Some(150), ..Default::default() })
+            .build()
+            .unwrap();
+        let list = |count| TranslatableTextList { texts: vec!["ja".to_string(); count], ..text_list(&[]) };
+
+        // The first batch of 50 texts is billed, the second one exceeds the quota of the server.
+        let error = deepl.translate(None, list(60)).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::QuotaExceeded(..)));
+        assert_eq!(server.character_count(), 100);
+
+        let error = deepl.translate(None, list(26)).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::BudgetExceeded(52, 50)));
+    }
+
+    #[test]
+    fn reset_before_reserve() {
+        let guard = BudgetGuard::new(CharacterBudget { max_characters: Some(10), ..Default::default() });
+        let usage: UsageInformation = serde_json::from_value(serde_json::json!({
+            "character_limit": 100,
+            "character_count": 0,
+        })).unwrap();
+        let too_long = text_list(&[&"ja".repeat(10)]);
+
+        // Another translation fails between fetching the usage and reserving.
+        guard.set_usage(&usage);
+        guard.reset();
+        assert!(!guard.reserve(&too_long).unwrap());
+        assert!(!guard.reserve(&too_long).unwrap());
+
+        guard.set_usage(&usage);
+        assert!(matches!(guard.reserve(&too_long).unwrap_err().kind(), ErrorKind::BudgetExceeded(20, 10)));
+        assert!(guard.reserve(&text_list(&["ja"])).unwrap());
+    }
+}
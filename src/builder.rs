@@ -0,0 +1,259 @@
+use crate::{budget::BudgetGuard, cache::SharedCache, default_server_url, Cassette, CharacterBudget, DeepL, ErrorKind, Result, RetryPolicy, TranslationCache};
+use error_chain::*;
+use std::time::Duration;
+
+/// Builder for [DeepL] instances with non-default connection settings.
+///
+/// # Example
+///
+/// ```rust
+/// use deepl_api::*;
+/// use std::time::Duration;
+///
+/// let deepl = DeepL::builder("YOUR_KEY".to_string())
+///     .server_url("http://localhost:3000")
+///     .timeout(Duration::from_secs(30))
+///     .user_agent("my-application/1.0")
+///     .build()
+///     .unwrap();
+/// ```
+pub struct DeepLBuilder {
+    api_key: String,
+    server_url: Option<String>,
+    config: ClientConfig,
+    settings: Settings,
+    client: Option<reqwest::blocking::Client>,
+    #[cfg(feature = "async")]
+    async_client: Option<reqwest::Client>,
+}
+
+/// Private settings that control the behaviour of the API methods.
+pub(crate) struct Settings {
+    pub(crate) retry_policy: RetryPolicy,
+    pub(crate) validate_glossary_languages: bool,
+    pub(crate) parallel_requests: usize,
+    pub(crate) cache: Option<SharedCache>,
+    pub(crate) cassette: Option<Cassette>,
+    pub(crate) budget: Option<BudgetGuard>,
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings {
+            retry_policy: RetryPolicy::none(),
+            validate_glossary_languages: false,
+            parallel_requests: 1,
+            cache: None,
+            cassette: None,
+            budget: None,
+        }
+    }
+}
+
+/// Private connection settings that are applied to the HTTP client.
+#[derive(Default)]
+struct ClientConfig {
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    user_agent: Option<String>,
+    proxy: Option<String>,
+}
+
+impl DeepLBuilder {
+    pub(crate) fn new(api_key: String) -> DeepLBuilder {
+        DeepLBuilder {
+            api_key,
+            server_url: None,
+            config: ClientConfig::default(),
+            settings: Settings::default(),
+            client: None,
+            #[cfg(feature = "async")]
+            async_client: None,
+        }
+    }
+
+    /// Use a custom server instead of the official DeepL API endpoints, e. g. `http://localhost:3000`
+    /// for a local stand-in or the URL of a corporate proxy. The API version path (`/v2`) is appended
+    /// automatically.
+    ///
+    /// By default, the server is selected based on the API key.
+    pub fn server_url(mut self, server_url: &str) -> DeepLBuilder {
+        self.server_url = Some(server_url.to_string());
+        self
+    }
+
+    /// Timeout for a complete request, from connecting until the response body has been read.
+    pub fn timeout(mut self, timeout: Duration) -> DeepLBuilder {
+        self.config.timeout = Some(timeout);
+        self
+    }
+
+    /// Timeout for establishing the connection to the server.
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> DeepLBuilder {
+        self.config.connect_timeout = Some(connect_timeout);
+        self
+    }
+
+    /// `User-Agent` header to send with every request.
+    pub fn user_agent(mut self, user_agent: &str) -> DeepLBuilder {
+        self.config.user_agent = Some(user_agent.to_string());
+        self
+    }
+
+    /// Send all requests via this proxy, e. g. `http://proxy.example.com:8080`.
+    pub fn proxy(mut self, proxy_url: &str) -> DeepLBuilder {
+        self.config.proxy = Some(proxy_url.to_string());
+        self
+    }
+
+    /// Retry requests that failed due to rate limiting or temporary server problems according to this
+    /// policy. By default, requests are not retried.
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> DeepLBuilder {
+        self.settings.retry_policy = retry_policy;
+        self
+    }
+
+    /// Check with [glossary_language_pairs](DeepL::glossary_language_pairs) whether the languages
+    /// are supported before creating a glossary, failing with an
+    /// [UnsupportedGlossaryLanguagePair](ErrorKind::UnsupportedGlossaryLanguagePair) error otherwise.
+    /// This costs an additional request per glossary creation.
+    pub fn validate_glossary_languages(mut self, validate: bool) -> DeepLBuilder {
+        self.settings.validate_glossary_languages = validate;
+        self
+    }
+
+    /// Send up to this many requests at the same time when a [translation](DeepL::translate) has to
+    /// be split into several requests. By default, the requests are sent one after another.
+    pub fn parallel_requests(mut self, parallel_requests: usize) -> DeepLBuilder {
+        self.settings.parallel_requests = parallel_requests.max(1);
+        self
+    }
+
+    /// Look up [translations](DeepL::translate) in this cache before sending them to DeepL, and store
+    /// new translations in it. By default, nothing is cached.
+    pub fn cache(mut self, cache: TranslationCache) -> DeepLBuilder {
+        self.settings.cache = Some(SharedCache::new(cache));
+        self
+    }
+
+    /// Refuse [translations](DeepL::translate) that would exceed this budget. By default, translations
+    /// are only limited by the quota of the account.
+    pub fn budget(mut self, budget: CharacterBudget) -> DeepLBuilder {
+        self.settings.budget = Some(BudgetGuard::new(budget));
+        self
+    }
+
+    /// Record all requests and responses in this [Cassette], or answer the requests from it
+    /// without contacting the server, depending on its mode.
+    pub fn cassette(mut self, cassette: Cassette) -> DeepLBuilder {
+        self.settings.cassette = Some(cassette);
+        self
+    }
+
+    /// Use an existing HTTP client, e. g. to share its connection pool with other parts of the
+    /// application. The timeout, user agent and proxy settings of this builder are ignored
+    /// in this case and have to be configured on the client instead.
+    pub fn client(mut self, client: reqwest::blocking::Client) -> DeepLBuilder {
+        self.client = Some(client);
+        self
+    }
+
+    /// Use an existing asynchronous HTTP client for [build_async](DeepLBuilder::build_async).
+    /// See [client](DeepLBuilder::client).
+    #[cfg(feature = "async")]
+    pub fn async_client(mut self, client: reqwest::Client) -> DeepLBuilder {
+        self.async_client = Some(client);
+        self
+    }
+
+    /// Create the [DeepL] instance. Fails with a [ConfigurationError](ErrorKind::ConfigurationError)
+    /// if the server or proxy URL is invalid.
+    pub fn build(self) -> Result<DeepL> {
+        let server_url = self.validated_server_url()?;
+        let client = match self.client {
+            Some(client) => client,
+            None => self.config.blocking_client()?,
+        };
+        Ok(DeepL {
+            api_key: self.api_key,
+            server_url,
+            client,
+            settings: self.settings,
+        })
+    }
+
+    /// Create an asynchronous [nonblocking::DeepL](crate::nonblocking::DeepL) instance with the same settings.
+    #[cfg(feature = "async")]
+    pub fn build_async(self) -> Result<crate::nonblocking::DeepL> {
+        let server_url = self.validated_server_url()?;
+        let client = match self.async_client {
+            Some(client) => client,
+            None => self.config.async_client()?,
+        };
+        Ok(crate::nonblocking::DeepL {
+            api_key: self.api_key,
+            server_url,
+            client,
+            settings: self.settings,
+        })
+    }
+
+    /// Private method that checks the custom server URL or falls back to the default server.
+    fn validated_server_url(&self) -> Result<String> {
+        match &self.server_url {
+            Some(server_url) => match reqwest::Url::parse(server_url) {
+                Ok(_) => Ok(server_url.trim_end_matches('/').to_string()),
+                Err(e) => bail!(ErrorKind::ConfigurationError(format!("invalid server URL '{}': {}", server_url, e))),
+            },
+            None => Ok(default_server_url(&self.api_key).to_string()),
+        }
+    }
+}
+
+impl ClientConfig {
+    /// Private method that checks the proxy URL.
+    fn proxy(&self) -> Result<Option<reqwest::Proxy>> {
+        match &self.proxy {
+            Some(proxy) => match reqwest::Proxy::all(proxy) {
+                Ok(proxy) => Ok(Some(proxy)),
+                Err(e) => bail!(ErrorKind::ConfigurationError(format!("invalid proxy URL '{}': {}", proxy, e))),
+            },
+            None => Ok(None),
+        }
+    }
+
+    fn blocking_client(&self) -> Result<reqwest::blocking::Client> {
+        let mut builder = reqwest::blocking::Client::builder();
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(connect_timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(connect_timeout);
+        }
+        if let Some(user_agent) = &self.user_agent {
+            builder = builder.user_agent(user_agent);
+        }
+        if let Some(proxy) = self.proxy()? {
+            builder = builder.proxy(proxy);
+        }
+        Ok(builder.build()?)
+    }
+
+    #[cfg(feature = "async")]
+    fn async_client(&self) -> Result<reqwest::Client> {
+        let mut builder = reqwest::Client::builder();
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(connect_timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(connect_timeout);
+        }
+        if let Some(user_agent) = &self.user_agent {
+            builder = builder.user_agent(user_agent);
+        }
+        if let Some(proxy) = self.proxy()? {
+            builder = builder.proxy(proxy);
+        }
+        Ok(builder.build()?)
+    }
+}
@@ -0,0 +1,418 @@
+use crate::{batch::BatchTranslations, translation_params, ErrorKind, Result, TranslatableTextList, TranslatedText, TranslationOptions};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+/// Number of stored entries after which the cache directory is scanned and pruned again.
+const PRUNE_INTERVAL: u64 = 100;
+
+/// A file-based cache for translations, which avoids paying for the same translation twice, e. g.
+/// across CI runs.
+///
+/// Each translated text is stored in a file of its own, keyed by the text, the languages and all
+/// [translation options](TranslationOptions). Note that changes to a glossary are not detected,
+/// as only its ID is part of the key.
+///
+/// Expired entries and the oldest entries beyond the size limit are removed after a client has stored
+/// its first translations, and then again every 100 stored translations. In between, the cache can
+/// grow beyond its size limit.
+///
+/// The cache works on a best effort basis: entries that cannot be read are treated as missing, and
+/// failures to store an entry are ignored, so that a broken cache never fails a translation.
+///
+/// If a text list is translated in several requests and one of them fails, the translations of the
+/// other requests are stored nevertheless before the error is returned, as they are billed as well.
+///
+/// # Example
+///
+/// ```rust
+/// use deepl_api::*;
+/// use std::time::Duration;
+///
+/// let deepl = DeepL::builder("YOUR_KEY".to_string())
+///     .cache(TranslationCache {
+///         ttl: Some(Duration::from_secs(7 * 24 * 60 * 60)),
+///         ..TranslationCache::new(".deepl-cache")
+///     })
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct TranslationCache {
+    /// Directory in which the entries are stored. It is created when the first entry is stored.
+    pub dir: PathBuf,
+    /// Entries older than this are not used anymore and removed. `None` keeps them until the size limit is reached.
+    pub ttl: Option<Duration>,
+    /// Upper limit for the total size of all entries in bytes. If it is exceeded, the oldest entries are removed.
+    pub max_size: Option<u64>,
+}
+
+/// Size information about a [TranslationCache].
+#[derive(Debug, PartialEq)]
+pub struct CacheStats {
+    /// Number of cached translations.
+    pub entries: u64,
+    /// Total size of all cached translations in bytes.
+    pub size: u64,
+}
+
+impl TranslationCache {
+    /// Cache in the given directory, keeping entries for 30 days and up to 100 MiB.
+    pub fn new(dir: impl Into<PathBuf>) -> TranslationCache {
+        TranslationCache {
+            dir: dir.into(),
+            ttl: Some(Duration::from_secs(30 * 24 * 60 * 60)),
+            max_size: Some(100 * 1024 * 1024),
+        }
+    }
+
+    /// Number and size of the cached translations.
+    pub fn stats(&self) -> Result<CacheStats> {
+        let entries = self.entries()?;
+        Ok(CacheStats {
+            entries: entries.len() as u64,
+            size: entries.iter().map(|entry| entry.size).sum(),
+        })
+    }
+
+    /// Remove all cached translations and return their number. Other files in the directory are kept.
+    pub fn clear(&self) -> Result<u64> {
+        let entries = self.entries()?;
+        for entry in &entries {
+            std::fs::remove_file(&entry.path)?;
+        }
+        Ok(entries.len() as u64)
+    }
+
+    /// Private method that looks up all texts of a list. Returns the lookup result and the list of
+    /// texts that still need to be translated.
+    fn lookup(
+        &self,
+        options: &Option<TranslationOptions>,
+        text_list: TranslatableTextList,
+    ) -> Result<(CacheLookup, TranslatableTextList)> {
+        let mut lookup = CacheLookup { keys: vec![], translations: vec![] };
+        let mut misses = TranslatableTextList {
+            source_language: text_list.source_language.clone(),
+            target_language: text_list.target_language.clone(),
+            texts: vec![],
+        };
+        for text in text_list.texts {
+            let key = cache_key(options, &misses, &text)?;
+            let translation = self.get(&key);
+            if translation.is_none() {
+                misses.texts.push(text);
+            }
+            lookup.keys.push(key);
+            lookup.translations.push(translation);
+        }
+        Ok((lookup, misses))
+    }
+
+    /// Private method that reads an entry, if it exists and has not expired yet.
+    fn get(&self, key: &str) -> Option<TranslatedText> {
+        let path = self.path(key);
+        let modified = std::fs::metadata(&path).ok()?.modified().ok()?;
+        if self.is_expired(modified) {
+            let _ = std::fs::remove_file(&path);
+            return None;
+        }
+        let mut translation: TranslatedText = serde_json::from_slice(&std::fs::read(&path).ok()?).ok()?;
+        // Cached translations are not billed again.
+        translation.billed_characters = translation.billed_characters.map(|_| 0);
+        Some(translation)
+    }
+
+    /// Private method that stores an entry. The file is written under a temporary name first,
+    /// so that concurrent readers never see partial entries. The name is unique, as other threads
+    /// and processes may store the same entry at the same time.
+    fn put(&self, key: &str, translation: &TranslatedText) -> Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let path = self.path(key);
+        let temp_path = path.with_extension(format!("{}-{:016x}.tmp", std::process::id(), rand::random::<u64>()));
+        std::fs::write(&temp_path, serde_json::to_vec(translation).unwrap_or_default())?;
+        std::fs::rename(temp_path, path)?;
+        Ok(())
+    }
+
+    /// Private method that removes expired entries and the oldest entries beyond the size limit.
+    fn prune(&self) -> Result<()> {
+        let mut entries = self.entries()?;
+        entries.sort_by_key(|entry| entry.modified);
+        let mut size: u64 = entries.iter().map(|entry| entry.size).sum();
+        for entry in entries {
+            let over_size = self.max_size.is_some_and(|max_size| size > max_size);
+            if !over_size && !self.is_expired(entry.modified) {
+                continue;
+            }
+            std::fs::remove_file(&entry.path)?;
+            size -= entry.size;
+        }
+        Ok(())
+    }
+
+    fn is_expired(&self, modified: SystemTime) -> bool {
+        match (self.ttl, modified.elapsed()) {
+            (Some(ttl), Ok(age)) => age > ttl,
+            _ => false,
+        }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+
+    /// Private method that lists all entries. A missing directory is an empty cache.
+    fn entries(&self) -> Result<Vec<CacheEntry>> {
+        let dir = match std::fs::read_dir(&self.dir) {
+            Ok(dir) => dir,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(e.into()),
+        };
+        let mut entries = vec![];
+        for dir_entry in dir {
+            let path = dir_entry?.path();
+            if !is_entry_file(&path) {
+                continue;
+            }
+            let metadata = std::fs::metadata(&path)?;
+            entries.push(CacheEntry {
+                path,
+                modified: metadata.modified()?,
+                size: metadata.len(),
+            });
+        }
+        Ok(entries)
+    }
+}
+
+/// Private wrapper around a [TranslationCache], shared by all requests of a client. It counts the
+/// stored entries, so that the cache directory is scanned for pruning only once in a while.
+#[derive(Clone, Debug)]
+pub(crate) struct SharedCache {
+    cache: TranslationCache,
+    stored: Arc<AtomicU64>,
+}
+
+impl SharedCache {
+    pub(crate) fn new(cache: TranslationCache) -> SharedCache {
+        SharedCache {
+            cache,
+            stored: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// See [TranslationCache::lookup].
+    pub(crate) fn lookup(
+        &self,
+        options: &Option<TranslationOptions>,
+        text_list: TranslatableTextList,
+    ) -> Result<(CacheLookup, TranslatableTextList)> {
+        self.cache.lookup(options, text_list)
+    }
+
+    /// Private method that counts newly stored entries and tells whether the cache has to be pruned,
+    /// which is the case for the first entries and then every [PRUNE_INTERVAL] entries.
+    fn count_stored(&self, entries: u64) -> bool {
+        let before = self.stored.fetch_add(entries, Ordering::Relaxed);
+        before == 0 || before / PRUNE_INTERVAL != (before + entries) / PRUNE_INTERVAL
+    }
+}
+
+struct CacheEntry {
+    path: PathBuf,
+    modified: SystemTime,
+    size: u64,
+}
+
+/// Private helper that recognizes entry files by their name, a SHA-256 hash with `.json` extension.
+fn is_entry_file(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension == "json")
+        && path.file_stem().and_then(|stem| stem.to_str()).is_some_and(|stem| {
+            stem.len() == 64 && stem.chars().all(|c| c.is_ascii_hexdigit())
+        })
+}
+
+/// Private helper that derives the cache key from the request parameters of a single text, which
+/// cover the languages and all translation options.
+fn cache_key(options: &Option<TranslationOptions>, text_list: &TranslatableTextList, text: &str) -> Result<String> {
+    let params = translation_params(options.clone(), TranslatableTextList {
+        source_language: text_list.source_language.clone(),
+        target_language: text_list.target_language.clone(),
+        texts: vec![text.to_string()],
+    })?;
+    let hash = Sha256::digest(serde_json::to_vec(&params).unwrap_or_default());
+    Ok(hash.iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+/// Private result of [TranslationCache::lookup], holding the cached translations.
+pub(crate) struct CacheLookup {
+    keys: Vec<String>,
+    translations: Vec<Option<TranslatedText>>,
+}
+
+impl CacheLookup {
+    /// Fill in the translations of the cache misses in the original order and store them in the cache.
+    /// If some of the misses failed to translate, the others are stored and the error is returned.
+    pub(crate) fn complete(self, cache: &SharedCache, translations: BatchTranslations) -> Result<Vec<TranslatedText>> {
+        let mut misses = translations.translations.into_iter();
+        let mut result = vec![];
+        let mut stored = 0;
+        for (key, cached) in self.keys.iter().zip(self.translations) {
+            let translation = match cached {
+                Some(translation) => Some(translation),
+                None => {
+                    let translation = misses.next().flatten();
+                    if let Some(translation) = &translation {
+                        stored += cache.cache.put(key, translation).is_ok() as u64;
+                    }
+                    translation
+                }
+            };
+            result.push(translation);
+        }
+        if stored > 0 && cache.count_stored(stored) {
+            let _ = cache.cache.prune();
+        }
+        if let Some(error) = translations.error {
+            return Err(error);
+        }
+        result.into_iter().collect::<Option<_>>().ok_or_else(|| ErrorKind::DeserializationError.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::text_list;
+    use assert_fs::TempDir;
+
+    fn translation(text: &str) -> TranslatedText {
+        TranslatedText {
+            detected_source_language: "DE".to_string(),
+            text: text.to_string(),
+            billed_characters: Some(text.len() as u64),
+            model_type_used: None,
+        }
+    }
+
+    /// The successful translations of all cache misses.
+    fn translations(texts: &[&str]) -> BatchTranslations {
+        BatchTranslations {
+            translations: texts.iter().map(|text| Some(translation(text))).collect(),
+            error: None,
+        }
+    }
+
+    /// A cache in a directory that does not exist yet.
+    fn temp_cache(temp_dir: &TempDir) -> TranslationCache {
+        TranslationCache::new(temp_dir.path().join("cache"))
+    }
+
+    #[test]
+    fn cache_key() {
+        let key = super::cache_key(&None, &text_list(&[]), "ja").unwrap();
+        assert_eq!(key.len(), 64);
+        assert_eq!(key, super::cache_key(&None, &text_list(&[]), "ja").unwrap());
+        assert_ne!(key, super::cache_key(&None, &text_list(&[]), "nein").unwrap());
+
+        let options = Some(TranslationOptions {
+            formality: Some(crate::Formality::More),
+            ..Default::default()
+        });
+        assert_ne!(key, super::cache_key(&options, &text_list(&[]), "ja").unwrap());
+        let mut other_target = text_list(&[]);
+        other_target.target_language = "EN-GB".into();
+        assert_ne!(key, super::cache_key(&None, &other_target, "ja").unwrap());
+    }
+
+    #[test]
+    fn lookup_and_complete() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = temp_cache(&temp_dir);
+        let shared = SharedCache::new(cache.clone());
+        assert_eq!(cache.stats().unwrap(), CacheStats { entries: 0, size: 0 });
+
+        let (lookup, misses) = cache.lookup(&None, text_list(&["ja", "nein"])).unwrap();
+        assert_eq!(misses.texts, vec!["ja", "nein"]);
+        let translated = lookup.complete(&shared, translations(&["yes", "no"])).unwrap();
+        assert_eq!(translated, vec![translation("yes"), translation("no")]);
+        assert_eq!(cache.stats().unwrap().entries, 2);
+        assert!(std::fs::read_dir(&cache.dir).unwrap().all(|entry| is_entry_file(&entry.unwrap().path())));
+
+        let (lookup, misses) = cache.lookup(&None, text_list(&["vielleicht", "nein", "ja"])).unwrap();
+        assert_eq!(misses.texts, vec!["vielleicht"]);
+        let translated = lookup.complete(&shared, translations(&["maybe"])).unwrap();
+        assert_eq!(translated.iter().map(|t| t.text.as_str()).collect::<Vec<_>>(), vec!["maybe", "no", "yes"]);
+        assert_eq!(translated[1].billed_characters, Some(0));
+
+        let (lookup, _) = cache.lookup(&None, text_list(&["gar nicht"])).unwrap();
+        assert!(lookup.complete(&shared, translations(&[])).is_err());
+
+        std::fs::write(cache.dir.join("README"), "not an entry").unwrap();
+        assert_eq!(cache.clear().unwrap(), 3);
+        assert_eq!(cache.stats().unwrap().entries, 0);
+        assert!(cache.dir.join("README").exists());
+    }
+
+    #[test]
+    fn failed_batch() {
+        let temp_dir = TempDir::new().unwrap();
+        let server = crate::mock::MockServer::start();
+        server.set_character_limit(250);
+        let deepl = server.builder().cache(temp_cache(&temp_dir)).build().unwrap();
+        let mut texts: Vec<String> = (0..50).map(|i| format!("ja {:02}", i)).collect();
+        texts.push("vielleicht".to_string());
+
+        // The first batch of 50 texts is billed, the second one exceeds the quota of the server.
+        let error = deepl.translate(None, TranslatableTextList { texts, ..text_list(&[]) }).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::QuotaExceeded(..)));
+        assert_eq!(temp_cache(&temp_dir).stats().unwrap().entries, 50);
+
+        let requests = server.request_count();
+        let translated = deepl.translate(None, text_list(&["ja 00", "ja 49"])).unwrap();
+        assert_eq!(translated.len(), 2);
+        assert_eq!(server.request_count(), requests);
+    }
+
+    #[test]
+    fn limits() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = TranslationCache {
+            max_size: Some(200),
+            ..temp_cache(&temp_dir)
+        };
+        for text in ["eins", "zwei", "drei", "vier"] {
+            // Each new client prunes the cache after storing its first translation.
+            let shared = SharedCache::new(cache.clone());
+            let (lookup, _) = shared.lookup(&None, text_list(&[text])).unwrap();
+            lookup.complete(&shared, translations(&[text])).unwrap();
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        let stats = cache.stats().unwrap();
+        assert!(stats.size <= 200 && stats.entries < 4, "{:?}", stats);
+        let (_, misses) = cache.lookup(&None, text_list(&["eins", "vier"])).unwrap();
+        assert_eq!(misses.texts, vec!["eins"]);
+
+        let cache = TranslationCache {
+            ttl: Some(Duration::ZERO),
+            ..cache
+        };
+        std::thread::sleep(Duration::from_millis(20));
+        let (_, misses) = cache.lookup(&None, text_list(&["vier"])).unwrap();
+        assert_eq!(misses.texts, vec!["vier"]);
+    }
+
+    #[test]
+    fn prune_interval() {
+        let shared = SharedCache::new(TranslationCache::new("unused"));
+        assert!(shared.count_stored(2));
+        assert!(!shared.count_stored(1));
+        assert!(!shared.count_stored(PRUNE_INTERVAL - 4));
+        assert!(shared.count_stored(1));
+        assert!(!shared.count_stored(1));
+        assert!(shared.count_stored(PRUNE_INTERVAL * 2));
+    }
+}
@@ -0,0 +1,282 @@
+use crate::{ErrorKind, Payload, Result};
+use error_chain::*;
+use reqwest::{header::HeaderMap, Method, StatusCode};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Placeholder for the API key in recorded requests.
+const REDACTED: &str = "[REDACTED]";
+
+/// Response headers that are recorded, as the client depends on them.
+const RECORDED_HEADERS: [&str; 2] = ["content-type", "retry-after"];
+
+/// A file with recorded HTTP interactions, to test against real DeepL responses without
+/// network access.
+///
+/// In [record](Cassette::record) mode, all requests are sent to the server as usual, and each request
+/// is written to the cassette file together with its response. In [replay](Cassette::replay) mode,
+/// nothing is sent; requests are answered with the recorded responses instead. A request that was
+/// not recorded fails with an [UnmatchedRequest](ErrorKind::UnmatchedRequest) error.
+///
+/// Requests are matched by method, path and parameters, and each recorded interaction is used only
+/// once, so repeated or retried requests are answered in the recorded order. The `Authorization`
+/// header is not recorded, and the API key is redacted wherever else it occurs in a request.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use deepl_api::*;
+///
+/// // Record once with a real API key ...
+/// let deepl = DeepL::builder(std::env::var("DEEPL_API_KEY").unwrap())
+///     .cassette(Cassette::record("tests/cassettes/translate.json"))
+///     .build()
+///     .unwrap();
+/// # let texts = || TranslatableTextList { source_language: None, target_language: "EN-US".into(), texts: vec![] };
+/// deepl.translate(None, texts()).unwrap();
+///
+/// // ... and replay it without.
+/// let deepl = DeepL::builder("unused".to_string())
+///     .cassette(Cassette::replay("tests/cassettes/translate.json").unwrap())
+///     .build()
+///     .unwrap();
+/// deepl.translate(None, texts()).unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct Cassette {
+    path: PathBuf,
+    recording: bool,
+    state: Arc<Mutex<CassetteState>>,
+}
+
+#[derive(Debug, Default)]
+struct CassetteState {
+    interactions: Vec<Interaction>,
+    used: Vec<bool>,
+}
+
+/// Content of a cassette file.
+#[derive(Serialize, Deserialize)]
+struct CassetteFile {
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Interaction {
+    request: RecordedRequest,
+    response: RecordedResponse,
+}
+
+/// The parts of a request that are used for matching.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RecordedRequest {
+    method: String,
+    path: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    params: Vec<(String, String)>,
+    #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
+    json: serde_json::Value,
+    /// File name and SHA-256 hash of an uploaded file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    file: Option<(String, String)>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct RecordedResponse {
+    status: u16,
+    #[serde(default)]
+    headers: Vec<(String, String)>,
+    body: String,
+    /// Binary bodies, e. g. of translated documents, are stored hex-encoded.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    binary: bool,
+}
+
+impl Cassette {
+    /// Record all interactions into this file. It is overwritten when the first response has been
+    /// received, and its directory must exist.
+    pub fn record(path: impl Into<PathBuf>) -> Cassette {
+        Cassette {
+            path: path.into(),
+            recording: true,
+            state: Arc::new(Mutex::new(CassetteState::default())),
+        }
+    }
+
+    /// Replay the interactions recorded in this file. Fails with a
+    /// [CassetteError](ErrorKind::CassetteError) if it cannot be parsed.
+    pub fn replay(path: impl Into<PathBuf>) -> Result<Cassette> {
+        let path = path.into();
+        let file: CassetteFile = match serde_json::from_slice(&std::fs::read(&path)?) {
+            Ok(file) => file,
+            Err(e) => bail!(ErrorKind::CassetteError(format!("cannot parse '{}': {}", path.display(), e))),
+        };
+        Ok(Cassette {
+            path,
+            recording: false,
+            state: Arc::new(Mutex::new(CassetteState {
+                used: vec![false; file.interactions.len()],
+                interactions: file.interactions,
+            })),
+        })
+    }
+
+    /// The cassette file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the cassette records interactions, rather than replaying them.
+    pub fn is_recording(&self) -> bool {
+        self.recording
+    }
+
+    /// Private method that returns the first unused response recorded for the request.
+    pub(crate) fn replay_response(&self, request: &RecordedRequest) -> Result<http::Response<Vec<u8>>> {
+        let mut state = self.state.lock().unwrap();
+        let CassetteState { interactions, used } = &mut *state;
+        let Some(index) = interactions.iter().zip(used.iter()).position(|(i, used)| !used && i.request == *request) else {
+            bail!(ErrorKind::UnmatchedRequest(format!("{} {}", request.method, request.path)));
+        };
+        used[index] = true;
+        interactions[index].response.to_http()
+    }
+
+    /// Private method that adds an interaction and writes the cassette file.
+    pub(crate) fn record_response(&self, request: RecordedRequest, status: StatusCode, headers: &HeaderMap, body: &[u8]) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state.interactions.push(Interaction {
+            request,
+            response: RecordedResponse::new(status, headers, body),
+        });
+        let file = CassetteFile { interactions: state.interactions.clone() };
+        std::fs::write(&self.path, serde_json::to_vec_pretty(&file).unwrap_or_default())?;
+        Ok(())
+    }
+}
+
+impl RecordedRequest {
+    pub(crate) fn new(method: &Method, path: &str, payload: Payload<'_>, api_key: &str) -> RecordedRequest {
+        let redact = |value: &str| match api_key.is_empty() {
+            true => value.to_string(),
+            false => value.replace(api_key, REDACTED),
+        };
+        let params = |params: &[(&str, String)]| params.iter().map(|(name, value)| (name.to_string(), redact(value))).collect();
+        let mut request = RecordedRequest {
+            method: method.to_string(),
+            path: path.to_string(),
+            params: vec![],
+            json: serde_json::Value::Null,
+            file: None,
+        };
+        match payload {
+            Payload::None => {}
+            Payload::Params(p) => request.params = params(p),
+            Payload::Json(body) => request.json = serde_json::from_str(&redact(&body.to_string())).unwrap_or_default(),
+            Payload::File(p, filename, content) => {
+                request.params = params(p);
+                request.file = Some((filename.to_string(), hex(&Sha256::digest(content))));
+            }
+        }
+        request
+    }
+}
+
+impl RecordedResponse {
+    fn new(status: StatusCode, headers: &HeaderMap, body: &[u8]) -> RecordedResponse {
+        let headers = RECORDED_HEADERS
+            .iter()
+            .filter_map(|name| Some((name.to_string(), headers.get(*name)?.to_str().ok()?.to_string())))
+            .collect();
+        let (body, binary) = match std::str::from_utf8(body) {
+            Ok(body) => (body.to_string(), false),
+            Err(_) => (hex(body), true),
+        };
+        RecordedResponse { status: status.as_u16(), headers, body, binary }
+    }
+
+    fn to_http(&self) -> Result<http::Response<Vec<u8>>> {
+        let body = match self.binary {
+            true => match unhex(&self.body) {
+                Some(body) => body,
+                None => bail!(ErrorKind::CassetteError("invalid binary response body".to_string())),
+            },
+            false => self.body.clone().into_bytes(),
+        };
+        let mut response = http::Response::builder().status(self.status);
+        for (name, value) in &self.headers {
+            response = response.header(name, value);
+        }
+        match response.body(body) {
+            Ok(response) => Ok(response),
+            Err(e) => bail!(ErrorKind::CassetteError(format!("invalid response: {}", e))),
+        }
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn unhex(hex: &str) -> Option<Vec<u8>> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{text_list, MockServer};
+    use crate::DeepL;
+
+    #[test]
+    fn recorded_request() {
+        let params = [("text", "ja".to_string()), ("key", "secret-key".to_string())];
+        let request = RecordedRequest::new(&Method::POST, "/v2/translate", Payload::Params(&params), "secret-key");
+        assert_eq!(request.params, vec![
+            ("text".to_string(), "ja".to_string()),
+            ("key".to_string(), REDACTED.to_string()),
+        ]);
+
+        let request = RecordedRequest::new(&Method::POST, "/v2/document", Payload::File(&[], "a.txt", b"ja"), "secret-key");
+        assert_eq!(request.file.unwrap().0, "a.txt");
+        assert_eq!(unhex(&hex(b"\x00\xffja")).unwrap(), b"\x00\xffja");
+    }
+
+    #[test]
+    fn record_and_replay() {
+        let temp_dir = assert_fs::TempDir::new().unwrap();
+        let path = temp_dir.path().join("cassette.json");
+        let server = MockServer::start();
+        server.inject_error(429, "Too many requests");
+        let deepl = server.builder()
+            .retry_policy(crate::RetryPolicy::default())
+            .cassette(Cassette::record(&path))
+            .build()
+            .unwrap();
+        assert_eq!(deepl.translate(None, text_list(&["ja"])).unwrap()[0].text, "yes");
+        assert_eq!(deepl.usage_information().unwrap().character_count, 2);
+        drop(server);
+
+        let recorded = std::fs::read_to_string(&path).unwrap();
+        assert!(!recorded.contains(MockServer::API_KEY));
+
+        let deepl = DeepL::builder("other_key".to_string())
+            .server_url("http://127.0.0.1:1")
+            .retry_policy(crate::RetryPolicy::default())
+            .cassette(Cassette::replay(&path).unwrap())
+            .build()
+            .unwrap();
+        assert_eq!(deepl.translate(None, text_list(&["ja"])).unwrap()[0].text, "yes");
+        assert_eq!(deepl.usage_information().unwrap().character_count, 2);
+
+        // Each interaction is replayed once, and other requests are not recorded.
+        let error = deepl.usage_information().unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::UnmatchedRequest(request) if request == "POST /v2/usage"));
+        assert!(deepl.translate(None, text_list(&["nein"])).is_err());
+    }
+}
@@ -0,0 +1,362 @@
+use crate::{DeepL, ErrorKind, Language, Result, TranslatableTextList, TranslatedText, TranslationOptions};
+use error_chain::*;
+
+/// Maximum size of a single text in bytes before it is split into chunks. Even if every byte has to
+/// be URL-encoded, a chunk still fits into one request.
+pub(crate) const MAX_CHUNK_SIZE: usize = 32 * 1024;
+
+/// Boundaries at which a text may be split, from the most to the least preferable.
+#[derive(Clone, Copy)]
+enum Boundary {
+    Paragraph,
+    Line,
+    Sentence,
+    Word,
+    /// The start or end of a tag, only used for texts with markup.
+    Tag,
+}
+
+impl Boundary {
+    const TEXT: [Boundary; 4] = [Boundary::Paragraph, Boundary::Line, Boundary::Sentence, Boundary::Word];
+    const MARKUP: [Boundary; 5] = [Boundary::Paragraph, Boundary::Line, Boundary::Sentence, Boundary::Word, Boundary::Tag];
+
+    /// Private method that checks whether the whitespace between two words, the first one ending with
+    /// `previous` and the second one starting with `next`, is such a boundary.
+    fn matches(self, previous: char, whitespace: &str, next: char) -> bool {
+        match self {
+            Boundary::Paragraph => whitespace.matches('\n').count() >= 2,
+            Boundary::Line => whitespace.contains('\n'),
+            Boundary::Sentence => {
+                (!whitespace.is_empty() && matches!(previous, '.' | '!' | '?' | '…'))
+                    || matches!(previous, '。' | '！' | '？')
+            }
+            Boundary::Word => !whitespace.is_empty(),
+            Boundary::Tag => previous == '>' || next == '<',
+        }
+    }
+}
+
+/// Private helper that returns the positions at which the text can be split at the given boundary.
+/// Whitespace stays with the preceding part. `allowed` tells for each byte position whether the
+/// text may be split there at all.
+fn split_points(text: &str, allowed: &[bool], boundary: Boundary) -> Vec<usize> {
+    let mut points = vec![];
+    let mut previous = None;
+    let mut whitespace_start = None;
+    for (i, c) in text.char_indices() {
+        if c.is_whitespace() {
+            whitespace_start.get_or_insert(i);
+            continue;
+        }
+        let whitespace = &text[whitespace_start.unwrap_or(i)..i];
+        if let Some(previous) = previous {
+            if allowed[i] && boundary.matches(previous, whitespace, c) {
+                points.push(i);
+            }
+        }
+        previous = Some(c);
+        whitespace_start = None;
+    }
+    points
+}
+
+/// Private helper that splits a text into consecutive chunks of at most `max_size` bytes, preferring
+/// paragraph over line, sentence and word boundaries. Concatenating the chunks yields the original text.
+///
+/// With `markup`, the text is never split inside a tag, e. g. at the whitespace between attributes,
+/// and the start and end of tags are used as boundaries before splitting within words. A chunk can
+/// then exceed `max_size` if a single tag does.
+pub(crate) fn split_text(text: &str, max_size: usize, markup: bool) -> Vec<&str> {
+    let mut allowed = vec![true; text.len() + 1];
+    if markup {
+        let mut tag_start = None;
+        for (i, c) in text.char_indices() {
+            match c {
+                '<' => tag_start = Some(i),
+                '>' => {
+                    if let Some(start) = tag_start.take() {
+                        allowed[start + 1..=i].fill(false);
+                    }
+                }
+                _ => {}
+            }
+        }
+        // An unterminated tag extends to the end of the text.
+        if let Some(start) = tag_start {
+            allowed[start + 1..text.len()].fill(false);
+        }
+    }
+    let boundaries: &[Boundary] = match markup {
+        true => &Boundary::MARKUP,
+        false => &Boundary::TEXT,
+    };
+    split_text_at(text, &allowed, max_size, boundaries)
+}
+
+fn split_text_at<'a>(text: &'a str, allowed: &[bool], max_size: usize, boundaries: &[Boundary]) -> Vec<&'a str> {
+    if text.len() <= max_size {
+        return vec![text];
+    }
+    let Some((&boundary, finer_boundaries)) = boundaries.split_first() else {
+        return split_chars(text, allowed, max_size);
+    };
+
+    let mut points = split_points(text, allowed, boundary);
+    points.push(text.len());
+    let mut chunks = vec![];
+    let (mut chunk_start, mut chunk_end) = (0, 0);
+    for point in points {
+        if point - chunk_start <= max_size {
+            chunk_end = point;
+            continue;
+        }
+        if chunk_end > chunk_start {
+            chunks.push(&text[chunk_start..chunk_end]);
+            chunk_start = chunk_end;
+        }
+        if point - chunk_start <= max_size {
+            chunk_end = point;
+        } else {
+            let allowed = &allowed[chunk_start..=point];
+            chunks.extend(split_text_at(&text[chunk_start..point], allowed, max_size, finer_boundaries));
+            chunk_start = point;
+            chunk_end = point;
+        }
+    }
+    if chunk_end > chunk_start {
+        chunks.push(&text[chunk_start..chunk_end]);
+    }
+    chunks
+}
+
+/// Private helper that splits a text without suitable boundaries at arbitrary allowed characters.
+fn split_chars<'a>(text: &'a str, allowed: &[bool], max_size: usize) -> Vec<&'a str> {
+    let mut chunks = vec![];
+    let (mut chunk_start, mut point) = (0, 0);
+    for (i, c) in text.char_indices() {
+        if allowed[i] {
+            point = i;
+        }
+        if i + c.len_utf8() - chunk_start > max_size && point > chunk_start {
+            chunks.push(&text[chunk_start..point]);
+            chunk_start = point;
+        }
+    }
+    chunks.push(&text[chunk_start..]);
+    chunks
+}
+
+/// A chunk of a text. The surrounding whitespace is not sent to DeepL, but kept as it is.
+#[derive(Debug, PartialEq)]
+struct Chunk {
+    leading: String,
+    content: String,
+    trailing: String,
+}
+
+impl Chunk {
+    fn new(chunk: &str) -> Chunk {
+        let content = chunk.trim();
+        let leading = &chunk[..chunk.len() - chunk.trim_start().len()];
+        Chunk {
+            leading: leading.to_string(),
+            content: content.to_string(),
+            trailing: chunk[leading.len() + content.len()..].to_string(),
+        }
+    }
+}
+
+/// Private helper that splits the texts of a list into chunks and stitches the translated chunks
+/// back together. Used by both the blocking and the asynchronous client.
+pub(crate) struct ChunkedTextList {
+    source_language: Option<Language>,
+    target_language: Language,
+    texts: Vec<Vec<Chunk>>,
+}
+
+impl ChunkedTextList {
+    /// Split the texts, which contain markup if tag handling is enabled in the `options`.
+    pub(crate) fn new(options: &Option<TranslationOptions>, text_list: TranslatableTextList, max_size: usize) -> ChunkedTextList {
+        let markup = options.as_ref().is_some_and(|options| options.tag_handling.is_some());
+        ChunkedTextList {
+            texts: text_list
+                .texts
+                .iter()
+                .map(|text| split_text(text, max_size, markup).into_iter().map(Chunk::new).collect())
+                .collect(),
+            source_language: text_list.source_language,
+            target_language: text_list.target_language,
+        }
+    }
+
+    /// The chunks that have to be translated, whitespace-only chunks are skipped.
+    pub(crate) fn text_list(&self) -> TranslatableTextList {
+        TranslatableTextList {
+            source_language: self.source_language.clone(),
+            target_language: self.target_language.clone(),
+            texts: self
+                .texts
+                .iter()
+                .flatten()
+                .filter(|chunk| !chunk.content.is_empty())
+                .map(|chunk| chunk.content.clone())
+                .collect(),
+        }
+    }
+
+    /// Combine the translations of the chunks returned by [text_list](ChunkedTextList::text_list)
+    /// into one translation per original text.
+    pub(crate) fn reassemble(self, translations: Vec<TranslatedText>) -> Result<Vec<TranslatedText>> {
+        let mut translations = translations.into_iter();
+        let mut result = vec![];
+        for chunks in self.texts {
+            let mut translated = TranslatedText {
+                detected_source_language: self.source_language.as_ref().map(Language::to_string).unwrap_or_default(),
+                text: String::new(),
+                billed_characters: None,
+                model_type_used: None,
+            };
+            let mut first = true;
+            for chunk in chunks {
+                translated.text.push_str(&chunk.leading);
+                if !chunk.content.is_empty() {
+                    let Some(translation) = translations.next() else {
+                        bail!(ErrorKind::DeserializationError);
+                    };
+                    if first {
+                        translated.detected_source_language = translation.detected_source_language;
+                        translated.model_type_used = translation.model_type_used;
+                        first = false;
+                    }
+                    if let Some(billed_characters) = translation.billed_characters {
+                        *translated.billed_characters.get_or_insert(0) += billed_characters;
+                    }
+                    translated.text.push_str(&translation.text);
+                }
+                translated.text.push_str(&chunk.trailing);
+            }
+            result.push(translated);
+        }
+        match translations.next() {
+            Some(_) => bail!(ErrorKind::DeserializationError),
+            None => Ok(result),
+        }
+    }
+}
+
+impl DeepL {
+    /// Translate [texts](TranslatableTextList) of any length. Works like [translate](DeepL::translate),
+    /// but texts that are too large for a single request are split at paragraph, line, sentence or word
+    /// boundaries, translated in chunks and put back together. The whitespace around the chunks, e. g.
+    /// the line breaks between paragraphs, is preserved as it is. With
+    /// [tag handling](TranslationOptions::tag_handling), texts are not split inside tags.
+    ///
+    /// Returns one translation per text. The billed characters of all chunks are added up.
+    pub fn translate_long_texts(
+        &self,
+        options: Option<TranslationOptions>,
+        text_list: TranslatableTextList,
+    ) -> Result<Vec<TranslatedText>> {
+        let chunked = ChunkedTextList::new(&options, text_list, MAX_CHUNK_SIZE);
+        let chunk_list = chunked.text_list();
+        let translations = match chunk_list.texts.is_empty() {
+            true => vec![],
+            false => self.translate(options, chunk_list)?,
+        };
+        chunked.reassemble(translations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_text() {
+        let text = "First paragraph. Second sentence.\n\nSecond paragraph.\nSecond line! Last sentence.";
+        assert_eq!(super::split_text(text, 100, false), vec![text]);
+        assert_eq!(super::split_text(text, 40, false), vec![
+            "First paragraph. Second sentence.\n\n",
+            "Second paragraph.\n",
+            "Second line! Last sentence.",
+        ]);
+        assert_eq!(super::split_text(text, 20, false), vec![
+            "First paragraph. ",
+            "Second sentence.\n\n",
+            "Second paragraph.\n",
+            "Second line! ",
+            "Last sentence.",
+        ]);
+        assert_eq!(super::split_text("Zweiundzwanzig", 5, false), vec!["Zweiu", "ndzwa", "nzig"]);
+        assert_eq!(super::split_text("äöü", 5, false), vec!["äö", "ü"]);
+        assert_eq!(super::split_text("日本語。日本語。", 12, false), vec!["日本語。", "日本語。"]);
+
+        let text = "  Lorem ipsum dolor sit amet.\n\n\n  Consectetur adipiscing elit.  \n".repeat(1000);
+        for max_size in [10, 100, 1000] {
+            let chunks = super::split_text(&text, max_size, false);
+            assert!(chunks.iter().all(|chunk| chunk.len() <= max_size));
+            assert_eq!(chunks.concat(), text);
+        }
+    }
+
+    #[test]
+    fn split_markup() {
+        let text = r#"<p class="a b">Ja nein.</p> <p>Ja.</p>"#;
+        assert_eq!(super::split_text(text, 16, false)[0], r#"<p class="a "#);
+        assert_eq!(super::split_text(text, 16, true), vec![r#"<p class="a b">"#, "Ja ", "nein.</p> ", "<p>Ja.</p>"]);
+        assert_eq!(super::split_text("<b>Zweiundzwanzig</b>", 16, true), vec!["<b>", "Zweiundzwanzig", "</b>"]);
+        assert_eq!(super::split_text("<tag>abcdef</tag>", 4, true), vec!["<tag>", "abcd", "ef", "</tag>"]);
+
+        let text = r#"<p title="ein langer Titel">Ja.</p> "#.repeat(100);
+        for max_size in [10, 100, 1000] {
+            let chunks = super::split_text(&text, max_size, true);
+            assert!(chunks.iter().all(|chunk| chunk.matches('<').count() == chunk.matches('>').count()));
+            assert_eq!(chunks.concat(), text);
+        }
+    }
+
+    #[test]
+    fn chunked_text_list() {
+        let text_list = TranslatableTextList {
+            source_language: Some("DE".into()),
+            target_language: "EN-US".into(),
+            texts: vec!["  Ja.\n\nNein.\n".to_string(), "\n".to_string(), "Ja.".to_string()],
+        };
+        let chunked = ChunkedTextList::new(&None, text_list, 8);
+        assert_eq!(chunked.text_list().texts, vec!["Ja.", "Nein.", "Ja."]);
+
+        let translation = |text: &str| TranslatedText {
+            detected_source_language: "DE".to_string(),
+            text: text.to_string(),
+            billed_characters: Some(3),
+            model_type_used: None,
+        };
+        let translated = chunked.reassemble(vec![translation("Yes."), translation("No."), translation("Yes.")]).unwrap();
+        assert_eq!(translated.iter().map(|t| t.text.as_str()).collect::<Vec<_>>(), vec!["  Yes.\n\nNo.\n", "\n", "Yes."]);
+        assert_eq!(translated[0].billed_characters, Some(6));
+        assert_eq!(translated[1].billed_characters, None);
+        assert_eq!(translated[1].detected_source_language, "DE");
+
+        let chunked = ChunkedTextList::new(&None, TranslatableTextList {
+            source_language: None,
+            target_language: "EN-US".into(),
+            texts: vec!["Ja.".to_string()],
+        }, 8);
+        assert!(chunked.reassemble(vec![]).is_err());
+    }
+
+    #[test]
+    fn translate_long_texts() {
+        let server = crate::mock::MockServer::start();
+        let deepl = server.deepl();
+        let text = "Ja.\n\nNein.\n".repeat(4000);
+        let translated = deepl.translate_long_texts(None, TranslatableTextList {
+            source_language: Some("DE".into()),
+            target_language: "EN-US".into(),
+            texts: vec![text],
+        }).unwrap();
+        assert_eq!(translated.len(), 1);
+        assert!(translated[0].text.starts_with("Yes."));
+        assert!(translated[0].text.ends_with('\n'));
+    }
+}
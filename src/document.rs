@@ -0,0 +1,304 @@
+use crate::{DeepL, ErrorKind, Formality, Language, Payload, Result};
+use error_chain::*;
+use reqwest::Method;
+use serde::Deserialize;
+use std::path::Path;
+use std::time::Duration;
+
+/// Custom [flags for the document translation request](https://www.deepl.com/docs-api/translate-documents/translate-document/).
+#[derive(Clone, Default)]
+pub struct DocumentTranslationOptions {
+    /// Sets whether the translated document should lean towards formal or informal language.
+    pub formality: Option<Formality>,
+    /// Specify the glossary to use for the translation.
+    pub glossary_id: Option<String>,
+    /// File extension of the desired output format, e. g. `docx` to receive a PDF as Word document.
+    /// By default, the translated document has the same format as the uploaded one.
+    pub output_format: Option<String>,
+}
+
+/// Holds a document that is supposed to be translated.
+#[derive(Debug)]
+pub struct TranslatableDocument {
+    /// Source language, if known. Will be auto-detected by the DeepL API
+    /// if not provided.
+    pub source_language: Option<Language>,
+    /// Target language (required).
+    pub target_language: Language,
+    /// File name of the document. DeepL determines the document type from its extension,
+    /// supported are e. g. `docx`, `pptx`, `xlsx`, `pdf`, `htm(l)` and `txt`.
+    pub filename: String,
+    /// Content of the document.
+    pub content: Vec<u8>,
+}
+
+impl TranslatableDocument {
+    /// Read the document to translate from a file.
+    pub fn from_file(
+        path: impl AsRef<Path>,
+        source_language: Option<Language>,
+        target_language: Language,
+    ) -> Result<TranslatableDocument> {
+        let path = path.as_ref();
+        Ok(TranslatableDocument {
+            source_language,
+            target_language,
+            filename: path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+            content: std::fs::read(path)?,
+        })
+    }
+}
+
+/// Identifies an uploaded document. Both values are required to check the status of the translation and
+/// to download the result, so they should be stored if the translation is supposed to be resumed later.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct DocumentHandle {
+    /// A unique ID assigned to the uploaded document.
+    pub document_id: String,
+    /// A unique key that is used to encrypt the uploaded document as well as the resulting translation on the server side.
+    pub document_key: String,
+}
+
+/// Translation state of an uploaded document.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DocumentState {
+    /// The translation job is waiting in line to be processed.
+    Queued,
+    /// The translation is currently ongoing.
+    Translating,
+    /// The translation is done and the translated document is ready for download.
+    Done,
+    /// An irrecoverable error occurred while translating the document.
+    Error,
+}
+
+/// Status of a document translation.
+#[derive(Debug, Deserialize)]
+pub struct DocumentStatus {
+    /// A unique ID assigned to the uploaded document.
+    pub document_id: String,
+    /// The current state of the translation.
+    pub status: DocumentState,
+    /// Estimated number of seconds until the translation is done, only available while translating.
+    pub seconds_remaining: Option<u64>,
+    /// The number of characters billed to the account, only available when the translation is done.
+    pub billed_characters: Option<u64>,
+    /// A short description of the error, if available.
+    pub error_message: Option<String>,
+}
+
+impl DocumentStatus {
+    /// Returns true if the translation is done or failed.
+    pub fn is_finished(&self) -> bool {
+        matches!(self.status, DocumentState::Done | DocumentState::Error)
+    }
+
+    /// Suggested waiting time before checking the status again, based on the remaining seconds.
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_secs(self.seconds_remaining.unwrap_or(1).clamp(1, 30))
+    }
+
+    /// Turn a failed translation into a [DocumentTranslationError](ErrorKind::DocumentTranslationError).
+    pub fn check(self) -> Result<DocumentStatus> {
+        match self.status {
+            DocumentState::Error => bail!(ErrorKind::DocumentTranslationError(
+                self.error_message.unwrap_or_default()
+            )),
+            _ => Ok(self),
+        }
+    }
+}
+
+/// Holds a translated document.
+#[derive(Debug)]
+pub struct TranslatedDocument {
+    /// The number of characters billed to the account.
+    pub billed_characters: Option<u64>,
+    /// Content of the translated document.
+    pub content: Vec<u8>,
+}
+
+/// Private helper that builds the request parameters for uploading a document. Fails if the target
+/// language cannot be used as such.
+pub(crate) fn document_params(
+    options: Option<DocumentTranslationOptions>,
+    document: &TranslatableDocument,
+) -> Result<Vec<(&'static str, String)>> {
+    let mut params = vec![("target_lang", document.target_language.to_target()?.to_string())];
+    if let Some(source_language) = &document.source_language {
+        params.push(("source_lang", source_language.to_source().to_string()));
+    }
+    if let Some(opt) = options {
+        if let Some(formality) = opt.formality {
+            params.push(("formality", formality.as_param().to_string()));
+        }
+        if let Some(glossary_id) = opt.glossary_id {
+            params.push(("glossary_id", glossary_id));
+        }
+        if let Some(output_format) = opt.output_format {
+            params.push(("output_format", output_format));
+        }
+    }
+    Ok(params)
+}
+
+/// Document translation. See also the [vendor documentation](https://www.deepl.com/docs-api/translate-documents/).
+impl DeepL {
+    /// Upload a [document](TranslatableDocument) for translation. You can pass in optional
+    /// [translation flags](DocumentTranslationOptions) if you need non-default behaviour.
+    ///
+    /// The translation happens in the background, use the returned [DocumentHandle] to check its status via
+    /// [document_status](DeepL::document_status) and to fetch the result via [download_document](DeepL::download_document).
+    pub fn upload_document(
+        &self,
+        options: Option<DocumentTranslationOptions>,
+        document: TranslatableDocument,
+    ) -> Result<DocumentHandle> {
+        let params = document_params(options, &document)?;
+        let res = self.http_request(
+            Method::POST,
+            "/v2/document",
+            Payload::File(&params, &document.filename, &document.content),
+        )?;
+
+        match res.json::<DocumentHandle>() {
+            Ok(content) => Ok(content),
+            _ => bail!(ErrorKind::DeserializationError),
+        }
+    }
+
+    /// Retrieve the current status of a document translation.
+    ///
+    /// Please take a look at the [vendor documentation](https://www.deepl.com/docs-api/translate-documents/get-document-status/) for details.
+    pub fn document_status(&self, handle: &DocumentHandle) -> Result<DocumentStatus> {
+        let res = self.http_request(
+            Method::POST,
+            &format!("/v2/document/{}", handle.document_id),
+            Payload::Params(&[("document_key", handle.document_key.clone())]),
+        )?;
+
+        match res.json::<DocumentStatus>() {
+            Ok(content) => Ok(content),
+            _ => bail!(ErrorKind::DeserializationError),
+        }
+    }
+
+    /// Wait until the translation of a document is done by polling its status. Fails with a
+    /// [DocumentTranslationError](ErrorKind::DocumentTranslationError) if the translation failed.
+    pub fn wait_for_document(&self, handle: &DocumentHandle) -> Result<DocumentStatus> {
+        loop {
+            let status = self.document_status(handle)?;
+            if status.is_finished() {
+                return status.check();
+            }
+            std::thread::sleep(status.poll_interval());
+        }
+    }
+
+    /// Download a translated document. This is only possible once, after the translation is done.
+    ///
+    /// Please take a look at the [vendor documentation](https://www.deepl.com/docs-api/translate-documents/download-translated-document/) for details.
+    pub fn download_document(&self, handle: &DocumentHandle) -> Result<Vec<u8>> {
+        let res = self.http_request(
+            Method::POST,
+            &format!("/v2/document/{}/result", handle.document_id),
+            Payload::Params(&[("document_key", handle.document_key.clone())]),
+        )?;
+
+        Ok(res.bytes()?.to_vec())
+    }
+
+    /// Translate a [document](TranslatableDocument): upload it, wait for the translation and download the result.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use deepl_api::*;
+    ///
+    /// let deepl = DeepL::new(std::env::var("DEEPL_API_KEY").unwrap());
+    /// let document = TranslatableDocument::from_file("manual.docx", None, "DE".into()).unwrap();
+    /// let translated = deepl.translate_document(None, document).unwrap();
+    /// std::fs::write("manual_de.docx", translated.content).unwrap();
+    /// ```
+    pub fn translate_document(
+        &self,
+        options: Option<DocumentTranslationOptions>,
+        document: TranslatableDocument,
+    ) -> Result<TranslatedDocument> {
+        let handle = self.upload_document(options, document)?;
+        let status = self.wait_for_document(&handle)?;
+        Ok(TranslatedDocument {
+            billed_characters: status.billed_characters,
+            content: self.download_document(&handle)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn document_params() {
+        let document = TranslatableDocument {
+            source_language: Some("EN".into()),
+            target_language: "DE".into(),
+            filename: "test.txt".to_string(),
+            content: b"Please go home.".to_vec(),
+        };
+        let options = DocumentTranslationOptions {
+            formality: Some(Formality::Less),
+            output_format: Some("docx".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(super::document_params(Some(options), &document).unwrap(), vec![
+            ("target_lang", "DE".to_string()),
+            ("source_lang", "EN".to_string()),
+            ("formality", "less".to_string()),
+            ("output_format", "docx".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn translate_document() {
+        let server = crate::mock::MockServer::start();
+        let deepl = server.deepl();
+        let document = TranslatableDocument {
+            source_language: Some("EN".into()),
+            target_language: "DE".into(),
+            filename: "test.txt".to_string(),
+            content: b"Please go home.".to_vec(),
+        };
+        let options = DocumentTranslationOptions {
+            formality: Some(Formality::More),
+            ..Default::default()
+        };
+        let translated = deepl.translate_document(Some(options), document).unwrap();
+        assert_eq!(String::from_utf8(translated.content).unwrap(), "Bitte gehen Sie nach Hause.");
+        assert!(translated.billed_characters.unwrap() > 0);
+    }
+
+    #[test]
+    fn document_status() {
+        let server = crate::mock::MockServer::start();
+        let deepl = server.deepl();
+        let document = TranslatableDocument {
+            source_language: None,
+            target_language: "DE".into(),
+            filename: "test.txt".to_string(),
+            content: b"Please go home.".to_vec(),
+        };
+        let handle = deepl.upload_document(None, document).unwrap();
+
+        let status = deepl.document_status(&handle).unwrap();
+        assert_eq!(status.status, DocumentState::Queued);
+        assert!(!status.is_finished());
+        let status = deepl.document_status(&handle).unwrap();
+        assert_eq!(status.status, DocumentState::Translating);
+        assert_eq!(status.seconds_remaining, Some(1));
+        let status = deepl.wait_for_document(&handle).unwrap();
+        assert_eq!(status.status, DocumentState::Done);
+        assert!(status.billed_characters.is_some());
+    }
+}
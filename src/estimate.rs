@@ -0,0 +1,107 @@
+use crate::{DeepL, Language, Result, TranslatableTextList, UsageInformation};
+use std::path::Path;
+
+/// The estimated cost of a translation job, compared with the remaining quota of the account.
+/// See [DeepL::estimate] and [DeepL::estimate_files].
+#[derive(Clone, Debug, PartialEq)]
+pub struct CostEstimate {
+    /// Number of characters that will be billed for the job.
+    pub characters: u64,
+    /// Characters remaining in the current billing period, before the job.
+    pub remaining_characters: u64,
+}
+
+impl CostEstimate {
+    pub(crate) fn new(characters: u64, usage_information: &UsageInformation) -> CostEstimate {
+        CostEstimate {
+            characters,
+            remaining_characters: usage_information.remaining_characters(),
+        }
+    }
+
+    /// Whether the job fits into the remaining quota.
+    pub fn fits_quota(&self) -> bool {
+        self.characters <= self.remaining_characters
+    }
+
+    /// Characters remaining in the current billing period after the job, zero if it does not fit.
+    pub fn remaining_characters_after(&self) -> u64 {
+        self.remaining_characters.saturating_sub(self.characters)
+    }
+}
+
+/// Count the characters of a text list like DeepL bills them: each Unicode character of the texts
+/// counts, including whitespace and markup.
+pub fn billable_characters(text_list: &TranslatableTextList) -> u64 {
+    text_list.texts.iter().map(|text| count_characters(text)).sum()
+}
+
+/// Count the billed characters of translating the text files into each of the target languages,
+/// which are billed separately.
+pub fn billable_file_characters(files: &[impl AsRef<Path>], target_languages: &[Language]) -> Result<u64> {
+    let mut characters = 0;
+    for file in files {
+        characters += count_characters(&std::fs::read_to_string(file)?);
+    }
+    Ok(characters * target_languages.len() as u64)
+}
+
+fn count_characters(text: &str) -> u64 {
+    text.chars().count() as u64
+}
+
+impl DeepL {
+    /// Estimate the billed characters for translating the [texts](TranslatableTextList) and compare
+    /// them with the remaining quota from [usage_information](DeepL::usage_information). Nothing is
+    /// translated, and translations that would be served from the [cache](crate::TranslationCache)
+    /// are counted as well.
+    pub fn estimate(&self, text_list: &TranslatableTextList) -> Result<CostEstimate> {
+        Ok(CostEstimate::new(billable_characters(text_list), &self.usage_information()?))
+    }
+
+    /// Like [estimate](DeepL::estimate), for translating UTF-8 text files into each of the target
+    /// languages.
+    pub fn estimate_files(&self, files: &[impl AsRef<Path>], target_languages: &[Language]) -> Result<CostEstimate> {
+        let characters = billable_file_characters(files, target_languages)?;
+        Ok(CostEstimate::new(characters, &self.usage_information()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{text_list, MockServer};
+    use assert_fs::{prelude::*, TempDir};
+
+    #[test]
+    fn billable_characters() {
+        assert_eq!(super::billable_characters(&text_list(&["Grüße, Welt!\n", "<p>ja</p>"])), 22);
+        assert_eq!(super::billable_characters(&text_list(&[])), 0);
+
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.child("input.txt");
+        file.write_str("Grüße").unwrap();
+        let target_languages: Vec<Language> = vec!["DE".into(), "FR".into(), "ES".into()];
+        assert_eq!(billable_file_characters(&[file.path(), file.path()], &target_languages).unwrap(), 30);
+        let missing = temp_dir.child("missing.txt");
+        assert!(billable_file_characters(&[missing.path()], &target_languages).is_err());
+    }
+
+    #[test]
+    fn estimate() {
+        let server = MockServer::start();
+        server.set_character_limit(20);
+        let deepl = server.deepl();
+        deepl.translate(None, text_list(&["ja nein"])).unwrap();
+
+        let estimate = deepl.estimate(&text_list(&["ja", "nein"])).unwrap();
+        assert_eq!(estimate, CostEstimate { characters: 6, remaining_characters: 13 });
+        assert!(estimate.fits_quota());
+        assert_eq!(estimate.remaining_characters_after(), 7);
+
+        let estimate = deepl.estimate(&text_list(&["Hallo Welt, hallo!"])).unwrap();
+        assert!(!estimate.fits_quota());
+        assert_eq!(estimate.remaining_characters_after(), 0);
+        assert_eq!(server.character_count(), 7);
+    }
+}
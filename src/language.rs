@@ -0,0 +1,178 @@
+use crate::{Error, ErrorKind, Result};
+use error_chain::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// A language code as used by DeepL, e. g. `DE`, `EN-US` or `ZH-HANS`.
+///
+/// Codes are normalized when converted from strings, so BCP-47 style input like `en_US`, `en-us`
+/// or `pt-BR` becomes `EN-US` and `PT-BR`, and `zh-CN` / `zh-TW` become `ZH-HANS` / `ZH-HANT`.
+/// Codes that are not known to this library are passed through as they are (but normalized), so
+/// that new DeepL languages can be used right away.
+///
+/// Source languages never have a regional variant. When a language is sent as source language,
+/// the variant is dropped automatically, see [to_source](Language::to_source). English and
+/// Portuguese, on the other hand, can only be used as target language with a variant, see
+/// [to_target](Language::to_target).
+///
+/// # Example
+///
+/// ```rust
+/// use deepl_api::*;
+///
+/// assert_eq!(Language::from("en_us").as_str(), "EN-US");
+/// assert_eq!(Language::from("en_us").to_source().as_str(), "EN");
+/// assert!("en us".parse::<Language>().is_err());
+/// assert!(Language::from("en").to_target().is_err());
+/// ```
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(from = "String", into = "String")]
+pub struct Language(String);
+
+impl Language {
+    /// The normalized code, e. g. `EN-US`.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The language without its regional variant, e. g. `EN` for `EN-US`.
+    pub fn base(&self) -> &str {
+        self.0.split('-').next().unwrap_or_default()
+    }
+
+    /// The regional variant or script, if any, e. g. `US` for `EN-US` or `HANS` for `ZH-HANS`.
+    pub fn variant(&self) -> Option<&str> {
+        self.0.split_once('-').map(|(_, variant)| variant)
+    }
+
+    /// The language as it can be used as source language, i. e. without regional variant.
+    pub fn to_source(&self) -> Language {
+        Language(self.base().to_string())
+    }
+
+    /// The language as it can be used as target language. Fails with a
+    /// [TargetLanguageWithoutVariant](ErrorKind::TargetLanguageWithoutVariant) error for `EN` and
+    /// `PT`, which need a regional variant like `EN-US` or `PT-BR`.
+    pub fn to_target(&self) -> Result<Language> {
+        match self.as_str() {
+            "EN" | "PT" => bail!(ErrorKind::TargetLanguageWithoutVariant(self.to_string())),
+            _ => Ok(self.clone()),
+        }
+    }
+
+    /// Private helper that brings a code into the form DeepL uses.
+    fn normalize(code: &str) -> String {
+        let code = code.trim().replace('_', "-").to_uppercase();
+        match code.as_str() {
+            "ZH-CN" | "ZH-SG" | "ZH-HANS-CN" => "ZH-HANS".to_string(),
+            "ZH-TW" | "ZH-HK" | "ZH-MO" | "ZH-HANT-TW" => "ZH-HANT".to_string(),
+            _ => code,
+        }
+    }
+}
+
+/// Normalizes the code without validating it, see [Language].
+impl From<&str> for Language {
+    fn from(code: &str) -> Language {
+        Language(Language::normalize(code))
+    }
+}
+
+/// Normalizes the code without validating it, see [Language].
+impl From<String> for Language {
+    fn from(code: String) -> Language {
+        Language::from(code.as_str())
+    }
+}
+
+impl From<Language> for String {
+    fn from(language: Language) -> String {
+        language.0
+    }
+}
+
+/// Normalizes the code and fails with an [InvalidLanguageCode](ErrorKind::InvalidLanguageCode)
+/// error if it is malformed, i. e. does not start with a two or three letter language.
+impl FromStr for Language {
+    type Err = Error;
+
+    fn from_str(code: &str) -> Result<Language> {
+        let language = Language::from(code);
+        let base = language.base();
+        let valid = (2..=3).contains(&base.len())
+            && base.chars().all(|c| c.is_ascii_alphabetic())
+            && language.0.split('-').all(|subtag| !subtag.is_empty() && subtag.chars().all(|c| c.is_ascii_alphanumeric()));
+        match valid {
+            true => Ok(language),
+            false => bail!(ErrorKind::InvalidLanguageCode(code.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize() {
+        for (input, expected) in [
+            ("de", "DE"),
+            ("EN", "EN"),
+            ("en_US", "EN-US"),
+            ("en-gb", "EN-GB"),
+            (" pt-BR ", "PT-BR"),
+            ("zh-Hans", "ZH-HANS"),
+            ("zh_CN", "ZH-HANS"),
+            ("zh-TW", "ZH-HANT"),
+            ("es-419", "ES-419"),
+            ("xx-future", "XX-FUTURE"),
+        ] {
+            assert_eq!(Language::from(input).as_str(), expected);
+        }
+    }
+
+    #[test]
+    fn source_and_variant() {
+        let language = Language::from("en-us");
+        assert_eq!(language.base(), "EN");
+        assert_eq!(language.variant(), Some("US"));
+        assert_eq!(language.to_source(), Language::from("EN"));
+        assert_eq!(Language::from("de").variant(), None);
+        assert_eq!(Language::from("zh-hant").to_source().as_str(), "ZH");
+    }
+
+    #[test]
+    fn target() {
+        assert_eq!(Language::from("en-gb").to_target().unwrap().as_str(), "EN-GB");
+        assert_eq!(Language::from("de").to_target().unwrap().as_str(), "DE");
+        for input in ["en", "PT"] {
+            let error = Language::from(input).to_target().unwrap_err();
+            assert!(matches!(error.kind(), ErrorKind::TargetLanguageWithoutVariant(code) if code.eq_ignore_ascii_case(input)));
+        }
+        let error = Language::from("en").to_target().unwrap_err();
+        assert_eq!(error.to_string(), "'EN' needs a regional variant like EN-US or EN-GB as target language.");
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!("pt_br".parse::<Language>().unwrap().as_str(), "PT-BR");
+        for input in ["", "e", "english", "en us", "en-", "1e"] {
+            let error = input.parse::<Language>().unwrap_err();
+            assert!(matches!(error.kind(), ErrorKind::InvalidLanguageCode(code) if code == input));
+        }
+    }
+
+    #[test]
+    fn serde() {
+        let language: Language = serde_json::from_str(r#""en""#).unwrap();
+        assert_eq!(language, Language::from("EN"));
+        assert_eq!(serde_json::to_string(&Language::from("en-us")).unwrap(), r#""EN-US""#);
+    }
+}
@@ -15,7 +15,7 @@
 //!
 //! # Example
 //!
-//! ```rust
+//! ```rust,no_run
 //! use deepl_api::*;
 //!
 //! // Create a DeepL instance for our account.
@@ -23,8 +23,8 @@
 //!
 //! // Translate Text
 //! let texts = TranslatableTextList {
-//!     source_language: Some("DE".to_string()),
-//!     target_language: "EN-US".to_string(),
+//!     source_language: Some("DE".into()),
+//!     target_language: "EN-US".into(),
 //!     texts: vec!("ja".to_string()),
 //! };
 //! let translated = deepl.translate(None, texts).unwrap();
@@ -35,6 +35,11 @@
 //! assert!(usage_information.character_limit > 0);
 //! ```
 //!
+//! # Async Support
+//!
+//! With the `async` cargo feature enabled, `nonblocking::DeepL` provides the same API
+//! as `async fn`s for use within an async runtime like `tokio`.
+//!
 //! # See Also
 //!
 //! The main API functions are documented in the [DeepL] struct.
@@ -42,15 +47,91 @@
 use chrono::{DateTime, Utc};
 use error_chain::*;
 use reqwest::{self, Method, blocking::Response};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+
+mod builder;
+pub use builder::DeepLBuilder;
+use builder::Settings;
+
+mod retry;
+pub use retry::RetryPolicy;
+
+mod language;
+pub use language::Language;
+
+mod batch;
+use batch::BatchTranslations;
+
+mod chunking;
+
+mod cache;
+pub use cache::{CacheStats, TranslationCache};
+
+mod translator;
+pub use translator::Translator;
+
+mod budget;
+pub use budget::CharacterBudget;
+
+mod estimate;
+pub use estimate::{billable_characters, billable_file_characters, CostEstimate};
+
+mod cassette;
+use cassette::RecordedRequest;
+pub use cassette::Cassette;
+
+mod document;
+pub use document::*;
+
+mod multilingual_glossary;
+pub use multilingual_glossary::*;
+
+#[cfg(feature = "async")]
+pub mod nonblocking;
+
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
 
 /// Information about API usage & limits for this account.
+///
+/// Apart from the character counters, the fields are only available for some account types.
 #[derive(Debug, Deserialize)]
 pub struct UsageInformation {
     /// How many characters can be translated per billing period, based on the account settings.
     pub character_limit: u64,
     /// How many characters were already translated in the current billing period.
     pub character_count: u64,
+    /// How many documents can be translated per billing period.
+    pub document_limit: Option<u64>,
+    /// How many documents were already translated in the current billing period.
+    pub document_count: Option<u64>,
+    /// How many documents can be translated by the team per billing period.
+    pub team_document_limit: Option<u64>,
+    /// How many documents were already translated by the team in the current billing period.
+    pub team_document_count: Option<u64>,
+    /// How many characters can be translated with this API key per billing period.
+    pub api_key_character_limit: Option<u64>,
+    /// How many characters were already translated with this API key in the current billing period.
+    pub api_key_character_count: Option<u64>,
+    /// Start of the current billing period.
+    pub start_time: Option<DateTime<Utc>>,
+    /// End of the current billing period.
+    pub end_time: Option<DateTime<Utc>>,
+}
+
+impl UsageInformation {
+    /// How many characters can still be translated in the current billing period.
+    pub fn remaining_characters(&self) -> u64 {
+        self.character_limit.saturating_sub(self.character_count)
+    }
+
+    /// Percentage of the character limit that was already used in the current billing period.
+    pub fn percent_used(&self) -> f64 {
+        match self.character_limit {
+            0 => 100.0,
+            limit => self.character_count as f64 * 100.0 / limit as f64,
+        }
+    }
 }
 
 /// Information about available languages.
@@ -64,6 +145,9 @@ pub struct LanguageInformation {
     pub language: String,
     /// English name of the language, e. g. `English (America)`.
     pub name: String,
+    /// Whether the [formality](TranslationOptions::formality) option is supported for this language.
+    /// Only available for target languages.
+    pub supports_formality: Option<bool>,
 }
 
 /// Translation option that controls the splitting of sentences before the translation.
@@ -82,14 +166,51 @@ pub enum SplitSentences {
 pub enum Formality {
     /// Default formality.
     Default,
-    /// Translate less formally.
+    /// Translate more formally. Fails for target languages that do not support formality.
     More,
-    /// Translate more formally.
+    /// Translate less formally. Fails for target languages that do not support formality.
     Less,
+    /// Translate more formally if the target language supports formality, otherwise use the default.
+    PreferMore,
+    /// Translate less formally if the target language supports formality, otherwise use the default.
+    PreferLess,
 }
 
-/// Custom [flags for the translation request](https://www.deepl.com/docs-api/translating-text/request/).
+impl Formality {
+    /// Private method that returns the value of the `formality` request parameter.
+    fn as_param(&self) -> &'static str {
+        match self {
+            Formality::Default => "default",
+            Formality::More => "more",
+            Formality::Less => "less",
+            Formality::PreferMore => "prefer_more",
+            Formality::PreferLess => "prefer_less",
+        }
+    }
+}
+
+/// Translation option that enables the handling of markup tags in the texts.
+#[derive(Clone)]
+pub enum TagHandling {
+    /// Treat the texts as XML.
+    Xml,
+    /// Treat the texts as HTML.
+    Html,
+}
+
+/// Translation option that selects the kind of model used for the translation.
 #[derive(Clone)]
+pub enum ModelType {
+    /// Use the model with the best translation quality, fail for languages which it does not support.
+    QualityOptimized,
+    /// Use the model with the best translation quality, fall back to the latency optimized model for languages it does not support.
+    PreferQualityOptimized,
+    /// Use the model with the lowest latency.
+    LatencyOptimized,
+}
+
+/// Custom [flags for the translation request](https://www.deepl.com/docs-api/translating-text/request/).
+#[derive(Clone, Default)]
 pub struct TranslationOptions {
     /// Sets whether the translation engine should first split the input into sentences. This is enabled by default.
     pub split_sentences: Option<SplitSentences>,
@@ -99,9 +220,30 @@ pub struct TranslationOptions {
     pub formality: Option<Formality>,
     /// Specify the glossary to use for the translation.
     pub glossary_id: Option<String>,
+    /// Sets which kind of tags should be handled. See also the
+    /// [vendor documentation](https://www.deepl.com/docs-api/xml/).
+    pub tag_handling: Option<TagHandling>,
+    /// Sets whether the XML structure is detected automatically to split sentences. This is enabled by default,
+    /// disable it to only split sentences at the `splitting_tags`.
+    pub outline_detection: Option<bool>,
+    /// XML tags which always cause splits.
+    pub splitting_tags: Option<Vec<String>>,
+    /// XML tags which never split sentences.
+    pub non_splitting_tags: Option<Vec<String>>,
+    /// XML tags whose content is not translated.
+    pub ignore_tags: Option<Vec<String>>,
+    /// Additional context that influences the translation, e. g. the surrounding paragraph or a note about
+    /// where a short text is used. The context itself is not translated and not billed.
+    pub context: Option<String>,
+    /// Sets which kind of model should be used for the translation.
+    pub model_type: Option<ModelType>,
+    /// Sets whether the number of billed characters is returned in [TranslatedText::billed_characters].
+    pub show_billed_characters: Option<bool>,
 }
 
 /// Format of glossary entries when creating a glossary.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum GlossaryEntriesFormat {
     /// tab-separated values
     Tsv,
@@ -119,14 +261,32 @@ pub struct Glossary {
     /// Indicates if the newly created glossary can already be used in translate requests. If the created glossary is not yet ready, you have to wait and check the ready status of the glossary before using it in a translate request.
     pub ready: bool,
     /// The language in which the source texts in the glossary are specified.
-    pub source_lang: String,
+    pub source_lang: Language,
     /// The language in which the target texts in the glossary are specified.
-    pub target_lang: String,
+    pub target_lang: Language,
     /// The creation time of the glossary.
     pub creation_time: DateTime<Utc>,
     /// The number of entries in the glossary.
     pub entry_count: u64,
 }
+/// Entries of a glossary, each consisting of a term in the source language and its translation.
+pub type GlossaryEntries = Vec<(String, String)>;
+
+/// A pair of languages that can be used for glossaries.
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct GlossaryLanguagePair {
+    /// The language in which the source texts in the glossary are specified.
+    pub source_lang: Language,
+    /// The language in which the target texts in the glossary are specified.
+    pub target_lang: Language,
+}
+
+// Only needed for JSON deserialization.
+#[derive(Debug, Deserialize)]
+struct GlossaryLanguagePairList {
+    supported_languages: Vec<GlossaryLanguagePair>,
+}
+
 // Representation of a glossary listing response.
 #[derive(Debug, Deserialize)]
 pub struct GlossaryListing {
@@ -139,20 +299,26 @@ pub struct GlossaryListing {
 pub struct TranslatableTextList {
     /// Source language, if known. Will be auto-detected by the DeepL API
     /// if not provided.
-    pub source_language: Option<String>,
+    pub source_language: Option<Language>,
     /// Target language (required).
-    pub target_language: String,
+    pub target_language: Language,
     /// List of texts that are supposed to be translated.
     pub texts: Vec<String>,
 }
 
 /// Holds one unit of translated text.
-#[derive(Debug, Deserialize, PartialEq)]
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
 pub struct TranslatedText {
     /// Source language. Holds the value provided, or otherwise the value that DeepL auto-detected.
     pub detected_source_language: String,
     /// Translated text.
     pub text: String,
+    /// The number of characters billed for this text, only available if
+    /// [TranslationOptions::show_billed_characters] was set.
+    pub billed_characters: Option<u64>,
+    /// The kind of model that was used for the translation, only available if
+    /// [TranslationOptions::model_type] was set.
+    pub model_type_used: Option<String>,
 }
 
 // Only needed for JSON deserialization.
@@ -168,6 +334,18 @@ struct ServerErrorMessage {
     detail: Option<String>
 }
 
+// Data that is sent along with a request.
+#[derive(Clone, Copy)]
+enum Payload<'a> {
+    None,
+    // Sent as query parameters for GET and DELETE requests, as form otherwise.
+    Params(&'a [(&'a str, String)]),
+    // Sent as JSON body.
+    Json(&'a serde_json::Value),
+    // Sent as multipart form with the parameters, file name and file content.
+    File(&'a [(&'a str, String)], &'a str, &'a [u8]),
+}
+
 /// The main API entry point representing a DeepL developer account with an associated API key.
 ///
 /// # Example
@@ -182,6 +360,9 @@ struct ServerErrorMessage {
 /// If you get an [AuthorizationError](ErrorKind::AuthorizationError), then something was wrong with your API key, for example.
 pub struct DeepL {
     api_key: String,
+    server_url: String,
+    client: reqwest::blocking::Client,
+    settings: Settings,
 }
 
 /// Implements the actual REST API. See also the [online documentation](https://www.deepl.com/docs-api/).
@@ -191,8 +372,22 @@ impl DeepL {
     ///
     /// Should you ever need to use more than one DeepL account in our program, then you can create one
     /// instance for each account / API key.
+    ///
+    /// Each instance keeps its own HTTP connection pool, so it should be reused for multiple calls
+    /// rather than being created for every translation.
     pub fn new(api_key: String) -> DeepL {
-        DeepL { api_key }
+        DeepL {
+            server_url: default_server_url(&api_key).to_string(),
+            api_key,
+            client: reqwest::blocking::Client::new(),
+            settings: Settings::default(),
+        }
+    }
+
+    /// Use this to create a DeepL API client instance with custom settings like a different
+    /// server URL, timeouts or a proxy. See [DeepLBuilder] for the available options.
+    pub fn builder(api_key: String) -> DeepLBuilder {
+        DeepLBuilder::new(api_key)
     }
 
     /// Private method that performs the HTTP calls.
@@ -200,55 +395,88 @@ impl DeepL {
         &self,
         method: Method,
         url: &str,
-        params: Option<&[(&str, std::string::String)]>,
+        payload: Payload<'_>,
     ) -> Result<reqwest::blocking::Response> {
 
-        let url = match self.api_key.ends_with(":fx") {
-            true  => format!("https://api-free.deepl.com/v2{}", url),
-            false => format!("https://api.deepl.com/v2{}", url),
-        };
+        let path = url;
+        let url = format!("{}{}", self.server_url, path);
 
-        let client = reqwest::blocking::Client::new();
-        let request = client.request(method.clone(), &url).header("Authorization", format!("DeepL-Auth-Key {}", self.api_key));
-
-        let response = match params {
-            Some(params) => {
-                match method {
-                    Method::GET => request.query(params).send(),
-                    Method::PATCH | Method::POST | Method::PUT => {
-                        request.form(params).send()
-                    },
-                    _ => unreachable!("Only GET, PATCH, POST and PUT are supported with params."),
-                }
-            },
-            None => request.send(),
-        };
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let response = match &self.settings.cassette {
+                Some(cassette) => self.cassette_request(cassette, &method, path, &url, payload),
+                None => self.send_request(&method, &url, payload).map_err(Error::from),
+            };
 
-        let res = match response {
-            Ok(response) if response.status().is_success() => response,
-            Ok(response) if response.status() == reqwest::StatusCode::UNAUTHORIZED => {
-                bail!(ErrorKind::AuthorizationError)
-            }
-            Ok(response) if response.status() == reqwest::StatusCode::FORBIDDEN => {
-                bail!(ErrorKind::AuthorizationError)
-            }
-            Ok(response) if response.status() == reqwest::StatusCode::NOT_FOUND => {
-                bail!(ErrorKind::NotFoundError)
-            }
-            // DeepL sends back error messages in the response body.
-            //   Try to fetch them to construct more helpful exceptions.
-            Ok(response) => {
-                let status = response.status();
-                match response.json::<ServerErrorMessage>() {
-                    Ok(server_error) => bail!(ErrorKind::ServerError(format!("{}: {}", server_error.message, server_error.detail.unwrap_or_default()))),
-                    _ => bail!(ErrorKind::ServerError(status.to_string())),
+            match response {
+                Ok(response) if response.status().is_success() => return Ok(response),
+                Ok(response) if self.settings.retry_policy.should_retry(response.status(), attempt) => {
+                    let delay = self.settings.retry_policy.backoff(attempt, response.headers());
+                    std::thread::sleep(delay);
+                }
+                // DeepL sends back error messages in the response body.
+                //   Try to fetch them to construct more helpful exceptions.
+                Ok(response) => {
+                    let status = response.status();
+                    let headers = response.headers().clone();
+                    let error = error_for_status(status, &headers, response.json::<ServerErrorMessage>().ok());
+                    return Err(self.settings.retry_policy.final_error(error.into(), attempt));
                 }
+                Err(e) => return Err(self.settings.retry_policy.final_error(e, attempt)),
             }
-            Err(e) => {
-                bail!(e)
+        }
+    }
+
+    /// Private method that sends a single request.
+    fn send_request(
+        &self,
+        method: &Method,
+        url: &str,
+        payload: Payload<'_>,
+    ) -> reqwest::Result<reqwest::blocking::Response> {
+        let request = self.client.request(method.clone(), url).header("Authorization", format!("DeepL-Auth-Key {}", self.api_key));
+
+        match payload {
+            Payload::None => request.send(),
+            Payload::Params(params) => match *method {
+                Method::GET | Method::DELETE => request.query(params).send(),
+                _ => request.form(params).send(),
+            },
+            Payload::Json(body) => request.json(body).send(),
+            Payload::File(params, filename, content) => {
+                let mut form = reqwest::blocking::multipart::Form::new();
+                for (name, value) in params {
+                    form = form.text(name.to_string(), value.clone());
+                }
+                let file = reqwest::blocking::multipart::Part::bytes(content.to_vec()).file_name(filename.to_string());
+                request.multipart(form.part("file", file)).send()
             }
-        };
-        Ok(res)
+        }
+    }
+
+    /// Private method that answers a request from the [Cassette], or sends it and records the response.
+    fn cassette_request(
+        &self,
+        cassette: &Cassette,
+        method: &Method,
+        path: &str,
+        url: &str,
+        payload: Payload<'_>,
+    ) -> Result<reqwest::blocking::Response> {
+        let request = RecordedRequest::new(method, path, payload, &self.api_key);
+        if !cassette.is_recording() {
+            return Ok(cassette.replay_response(&request)?.into());
+        }
+        let response = self.send_request(method, url, payload)?;
+        let (status, headers) = (response.status(), response.headers().clone());
+        let body = response.bytes()?.to_vec();
+        cassette.record_response(request, status, &headers, &body)?;
+
+        let mut response = http::Response::new(body);
+        *response.status_mut() = status;
+        *response.headers_mut() = headers;
+        Ok(response.into())
     }
 
     /// Retrieve information about API usage & limits.
@@ -256,36 +484,34 @@ impl DeepL {
     ///
     /// See also the [vendor documentation](https://www.deepl.com/docs-api/other-functions/monitoring-usage/).
     pub fn usage_information(&self) -> Result<UsageInformation> {
-        let res = self.http_request(Method::POST, "/usage", None)?;
+        let res = self.http_request(Method::POST, "/v2/usage", Payload::None)?;
 
         match res.json::<UsageInformation>() {
-            Ok(content) => return Ok(content),
-            _ => {
-                bail!(ErrorKind::DeserializationError);
-            }
-        };
+            Ok(content) => Ok(content),
+            _ => bail!(ErrorKind::DeserializationError),
+        }
     }
 
     /// Retrieve all currently available source languages.
     ///
     /// See also the [vendor documentation](https://www.deepl.com/docs-api/other-functions/listing-supported-languages/).
     pub fn source_languages(&self) -> Result<LanguageList> {
-        return self.languages("source");
+        self.languages("source")
     }
 
     /// Retrieve all currently available target languages.
     ///
     /// See also the [vendor documentation](https://www.deepl.com/docs-api/other-functions/listing-supported-languages/).
     pub fn target_languages(&self) -> Result<LanguageList> {
-        return self.languages("target");
+        self.languages("target")
     }
 
     /// Private method to make the API calls for the language lists.
     fn languages(&self, language_type: &str) -> Result<LanguageList> {
-            let res = self.http_request(Method::POST, "/languages", Some(&[("type", language_type.to_string())]))?;
+        let res = self.http_request(Method::POST, "/v2/languages", Payload::Params(&[("type", language_type.to_string())]))?;
 
         match res.json::<LanguageList>() {
-            Ok(content) => return Ok(content),
+            Ok(content) => Ok(content),
             _ => bail!(ErrorKind::DeserializationError),
         }
     }
@@ -293,6 +519,13 @@ impl DeepL {
     /// Translate one or more [text chunks](TranslatableTextList) at once. You can pass in optional
     /// [translation flags](TranslationOptions) if you need non-default behaviour.
     ///
+    /// Lists that exceed the limits of DeepL for a single request (50 texts, 128 KiB) are split into
+    /// several requests automatically, which can be sent in parallel via
+    /// [DeepLBuilder::parallel_requests]. The translations are returned in the original order.
+    ///
+    /// If a [TranslationCache] is configured via [DeepLBuilder::cache], only texts that are not cached
+    /// yet are sent to DeepL.
+    ///
     /// Please see the parameter documentation and the
     /// [vendor documentation](https://www.deepl.com/docs-api/translating-text/) for details.
     pub fn translate(
@@ -300,51 +533,81 @@ impl DeepL {
         options: Option<TranslationOptions>,
         text_list: TranslatableTextList,
     ) -> Result<Vec<TranslatedText>> {
-        let mut query = vec![
-            ("target_lang", text_list.target_language),
-        ];
-        if let Some(source_language_content) = text_list.source_language {
-            query.push(("source_lang", source_language_content));
-        }
-        for text in text_list.texts {
-            query.push(("text", text));
-        }
-        if let Some(opt) = options {
-            if let Some(split_sentences) = opt.split_sentences {
-                query.push((
-                    "split_sentences",
-                    match split_sentences {
-                        SplitSentences::None => "0".to_string(),
-                        SplitSentences::PunctuationAndNewlines => "1".to_string(),
-                        SplitSentences::Punctuation => "nonewlines".to_string(),
-                    },
-                ));
-            }
-            if let Some(preserve_formatting) = opt.preserve_formatting {
-                query.push((
-                    "preserve_formatting",
-                    match preserve_formatting {
-                        false => "0".to_string(),
-                        true => "1".to_string(),
-                    },
-                ));
-            }
-            if let Some(formality) = opt.formality {
-                query.push((
-                    "formality",
-                    match formality {
-                        Formality::Default => "default".to_string(),
-                        Formality::More => "more".to_string(),
-                        Formality::Less => "less".to_string(),
-                    },
-                ));
-            }
-            if let Some(glossary_id) = opt.glossary_id {
-                query.push(("glossary_id", glossary_id));
+        let Some(cache) = &self.settings.cache else {
+            return self.translate_uncached(options, text_list)?.into_result();
+        };
+        let (lookup, misses) = cache.lookup(&options, text_list)?;
+        let translations = match misses.texts.is_empty() {
+            true => BatchTranslations::default(),
+            false => self.translate_uncached(options, misses)?,
+        };
+        lookup.complete(cache, translations)
+    }
+
+    /// Private method that translates a text list in batches, bypassing the cache.
+    fn translate_uncached(
+        &self,
+        options: Option<TranslationOptions>,
+        text_list: TranslatableTextList,
+    ) -> Result<BatchTranslations> {
+        let Some(budget) = &self.settings.budget else {
+            return self.translate_batches(options, text_list);
+        };
+        while !budget.reserve(&text_list)? {
+            budget.set_usage(&self.usage_information()?);
+        }
+        let translations = self.translate_batches(options, text_list)?;
+        if translations.error.is_some() {
+            // Some of the batches may have been billed, so the usage has to be fetched again.
+            budget.reset();
+        }
+        Ok(translations)
+    }
+
+    /// Private method that translates a text list in batches. No further batches are sent once a
+    /// batch failed.
+    fn translate_batches(
+        &self,
+        options: Option<TranslationOptions>,
+        text_list: TranslatableTextList,
+    ) -> Result<BatchTranslations> {
+        let mut batches = batch::split_text_list(&options, text_list)?.into_iter().peekable();
+        let mut translations = BatchTranslations::default();
+        while batches.peek().is_some() && translations.error.is_none() {
+            let group: Vec<TranslatableTextList> = batches.by_ref().take(self.settings.parallel_requests).collect();
+            let sizes: Vec<usize> = group.iter().map(|batch| batch.texts.len()).collect();
+            let results: Vec<Result<Vec<TranslatedText>>> = match group.len() {
+                1 => group.into_iter().map(|batch| self.translate_batch(options.clone(), batch)).collect(),
+                _ => std::thread::scope(|scope| {
+                    let handles: Vec<_> = group
+                        .into_iter()
+                        .map(|batch| {
+                            let options = options.clone();
+                            scope.spawn(move || self.translate_batch(options, batch))
+                        })
+                        .collect();
+                    handles
+                        .into_iter()
+                        .map(|handle| handle.join().unwrap_or_else(|e| std::panic::resume_unwind(e)))
+                        .collect()
+                }),
+            };
+            for (size, result) in sizes.into_iter().zip(results) {
+                translations.push(size, result);
             }
         }
+        Ok(translations)
+    }
+
+    /// Private method that translates a text list which fits into a single request.
+    fn translate_batch(
+        &self,
+        options: Option<TranslationOptions>,
+        text_list: TranslatableTextList,
+    ) -> Result<Vec<TranslatedText>> {
+        let query = translation_params(options, text_list)?;
 
-        let res = self.http_request(Method::POST, "/translate", Some(&query))?;
+        let res = self.http_request(Method::POST, "/v2/translate", Payload::Params(&query))?;
 
         match res.json::<TranslatedTextList>() {
             Ok(content) => Ok(content.translations),
@@ -354,25 +617,22 @@ impl DeepL {
 
     /// Create a glossary.
     ///
+    /// If enabled via [DeepLBuilder::validate_glossary_languages], the language pair is checked before.
+    ///
     /// Please take a look at the [vendor documentation](https://www.deepl.com/de/docs-api/glossaries/create-glossary/) for details.
     pub fn create_glossary(
         &self,
         name: String,
-        source_lang: String,
-        target_lang: String,
+        source_lang: Language,
+        target_lang: Language,
         entries: String,
         entries_format: GlossaryEntriesFormat
     ) -> Result<Glossary> {
-        let res = self.http_request(Method::POST, "/glossaries", Some(&[
-            ("name", name),
-            ("source_lang", source_lang),
-            ("target_lang", target_lang),
-            ("entries", entries),
-            ("entries_format", match entries_format {
-                GlossaryEntriesFormat::Tsv => "tsv".to_string(),
-                GlossaryEntriesFormat::Csv => "csv".to_string(),
-            })])
-        )?;
+        if self.settings.validate_glossary_languages {
+            check_glossary_language_pair(&self.glossary_language_pairs()?, &source_lang, &target_lang)?;
+        }
+        let params = glossary_params(name, source_lang, target_lang, entries, entries_format);
+        let res = self.http_request(Method::POST, "/v2/glossaries", Payload::Params(&params))?;
 
         match res.json::<Glossary>() {
             Ok(content) => Ok(content),
@@ -380,11 +640,23 @@ impl DeepL {
         }
     }
 
+    /// Retrieve the language pairs that are supported for glossaries.
+    ///
+    /// Please take a look at the [vendor documentation](https://www.deepl.com/de/docs-api/glossaries/list-glossary-languages/) for details.
+    pub fn glossary_language_pairs(&self) -> Result<Vec<GlossaryLanguagePair>> {
+        let res = self.http_request(Method::GET, "/v2/glossary-language-pairs", Payload::None)?;
+
+        match res.json::<GlossaryLanguagePairList>() {
+            Ok(content) => Ok(content.supported_languages),
+            _ => bail!(ErrorKind::DeserializationError),
+        }
+    }
+
     /// List all glossaries.
     ///
     /// Please take a look at the [vendor documentation](https://www.deepl.com/de/docs-api/glossaries/list-glossaries/) for details.
     pub fn list_glossaries(&self) -> Result<GlossaryListing> {
-        let res = self.http_request(Method::GET, "/glossaries", None)?;
+        let res = self.http_request(Method::GET, "/v2/glossaries", Payload::None)?;
 
         match res.json::<GlossaryListing>() {
             Ok(content) => Ok(content),
@@ -396,28 +668,202 @@ impl DeepL {
     ///
     /// Please take a look at the [vendor documentation](https://www.deepl.com/de/docs-api/glossaries/delete-glossary/) for details.
     pub fn delete_glossary(&self, glossary_id: String) -> Result<Response> {
-        self.http_request(Method::DELETE, &format!("/glossaries/{}", glossary_id), None)
+        self.http_request(Method::DELETE, &format!("/v2/glossaries/{}", glossary_id), Payload::None)
     }
 
     /// Retrieve Glossary Details.
     ///
     /// Please take a look at the [vendor documentation](https://www.deepl.com/de/docs-api/glossaries/get-glossary/) for details.
     pub fn get_glossary(&self, glossary_id: String) -> Result<Glossary> {
-        let res = self.http_request(Method::GET, &format!("/glossaries/{}", glossary_id), None)?;
+        let res = self.http_request(Method::GET, &format!("/v2/glossaries/{}", glossary_id), Payload::None)?;
 
         match res.json::<Glossary>() {
             Ok(content) => Ok(content),
             _ => bail!(ErrorKind::DeserializationError),
         }
     }
+
+    /// Retrieve the entries of a glossary.
+    ///
+    /// Please take a look at the [vendor documentation](https://www.deepl.com/de/docs-api/glossaries/get-glossary-entries/) for details.
+    pub fn get_glossary_entries(&self, glossary_id: String) -> Result<GlossaryEntries> {
+        let res = self.http_request(Method::GET, &format!("/v2/glossaries/{}/entries", glossary_id), Payload::None)?;
+
+        match res.text() {
+            Ok(content) => parse_glossary_entries(&content),
+            _ => bail!(ErrorKind::DeserializationError),
+        }
+    }
+}
+
+/// Private helper that determines the API server for an API key. Keys of the DeepL API Free
+/// plan end with `:fx` and have to use a different server.
+fn default_server_url(api_key: &str) -> &'static str {
+    match api_key.ends_with(":fx") {
+        true  => "https://api-free.deepl.com",
+        false => "https://api.deepl.com",
+    }
+}
+
+/// Private helper that maps an unsuccessful HTTP status to an error, using the message
+/// from the response body if DeepL provided one.
+fn error_for_status(
+    status: reqwest::StatusCode,
+    headers: &reqwest::header::HeaderMap,
+    server_error: Option<ServerErrorMessage>,
+) -> ErrorKind {
+    let message = match server_error {
+        Some(server_error) => format!("{}: {}", server_error.message, server_error.detail.unwrap_or_default()),
+        None => status.to_string(),
+    };
+    match status.as_u16() {
+        401 | 403 => ErrorKind::AuthorizationError,
+        404 => ErrorKind::NotFoundError,
+        413 => ErrorKind::PayloadTooLarge(status.as_u16(), message),
+        429 | 529 => ErrorKind::RateLimited(status.as_u16(), message, retry::retry_after(headers)),
+        456 => ErrorKind::QuotaExceeded(status.as_u16(), message),
+        _ => ErrorKind::ServerError(message),
+    }
+}
+
+/// Private helper that checks whether a glossary can be created for the given languages.
+fn check_glossary_language_pair(pairs: &[GlossaryLanguagePair], source_lang: &Language, target_lang: &Language) -> Result<()> {
+    let (source_lang, target_lang) = (source_lang.to_source(), target_lang.to_source());
+    let supported = pairs.iter().any(|pair| {
+        pair.source_lang == source_lang && pair.target_lang == target_lang
+    });
+    match supported {
+        true => Ok(()),
+        false => bail!(ErrorKind::UnsupportedGlossaryLanguagePair(source_lang.to_string(), target_lang.to_string())),
+    }
+}
+
+/// Private helper that parses glossary entries in TSV format.
+fn parse_glossary_entries(tsv: &str) -> Result<GlossaryEntries> {
+    let mut entries = GlossaryEntries::new();
+    for line in tsv.lines().filter(|line| !line.is_empty()) {
+        match line.split_once('\t') {
+            Some((source, target)) => entries.push((source.to_string(), target.to_string())),
+            None => bail!(ErrorKind::DeserializationError),
+        }
+    }
+    Ok(entries)
 }
 
-mod errors {
-    use error_chain::*;
-    error_chain! {}
+/// Private helper that builds the request parameters for a translation. Fails if the target
+/// language cannot be used as such.
+fn translation_params(
+    options: Option<TranslationOptions>,
+    text_list: TranslatableTextList,
+) -> Result<Vec<(&'static str, String)>> {
+    let mut query = vec![
+        ("target_lang", text_list.target_language.to_target()?.into()),
+    ];
+    if let Some(source_language_content) = text_list.source_language {
+        query.push(("source_lang", source_language_content.to_source().into()));
+    }
+    for text in text_list.texts {
+        query.push(("text", text));
+    }
+    if let Some(opt) = options {
+        if let Some(split_sentences) = opt.split_sentences {
+            query.push((
+                "split_sentences",
+                match split_sentences {
+                    SplitSentences::None => "0".to_string(),
+                    SplitSentences::PunctuationAndNewlines => "1".to_string(),
+                    SplitSentences::Punctuation => "nonewlines".to_string(),
+                },
+            ));
+        }
+        if let Some(preserve_formatting) = opt.preserve_formatting {
+            query.push((
+                "preserve_formatting",
+                match preserve_formatting {
+                    false => "0".to_string(),
+                    true => "1".to_string(),
+                },
+            ));
+        }
+        if let Some(formality) = opt.formality {
+            query.push(("formality", formality.as_param().to_string()));
+        }
+        if let Some(glossary_id) = opt.glossary_id {
+            query.push(("glossary_id", glossary_id));
+        }
+        if let Some(tag_handling) = opt.tag_handling {
+            query.push((
+                "tag_handling",
+                match tag_handling {
+                    TagHandling::Xml => "xml".to_string(),
+                    TagHandling::Html => "html".to_string(),
+                },
+            ));
+        }
+        if let Some(outline_detection) = opt.outline_detection {
+            query.push((
+                "outline_detection",
+                match outline_detection {
+                    false => "0".to_string(),
+                    true => "1".to_string(),
+                },
+            ));
+        }
+        if let Some(splitting_tags) = opt.splitting_tags {
+            query.push(("splitting_tags", splitting_tags.join(",")));
+        }
+        if let Some(non_splitting_tags) = opt.non_splitting_tags {
+            query.push(("non_splitting_tags", non_splitting_tags.join(",")));
+        }
+        if let Some(ignore_tags) = opt.ignore_tags {
+            query.push(("ignore_tags", ignore_tags.join(",")));
+        }
+        if let Some(context) = opt.context {
+            query.push(("context", context));
+        }
+        if let Some(model_type) = opt.model_type {
+            query.push((
+                "model_type",
+                match model_type {
+                    ModelType::QualityOptimized => "quality_optimized".to_string(),
+                    ModelType::PreferQualityOptimized => "prefer_quality_optimized".to_string(),
+                    ModelType::LatencyOptimized => "latency_optimized".to_string(),
+                },
+            ));
+        }
+        if let Some(show_billed_characters) = opt.show_billed_characters {
+            query.push((
+                "show_billed_characters",
+                match show_billed_characters {
+                    false => "0".to_string(),
+                    true => "1".to_string(),
+                },
+            ));
+        }
+    }
+    Ok(query)
 }
 
-pub use errors::*;
+/// Private helper that builds the request parameters for creating a glossary.
+fn glossary_params(
+    name: String,
+    source_lang: Language,
+    target_lang: Language,
+    entries: String,
+    entries_format: GlossaryEntriesFormat
+) -> [(&'static str, String); 5] {
+    // Glossaries are defined for languages without regional variants.
+    [
+        ("name", name),
+        ("source_lang", source_lang.to_source().into()),
+        ("target_lang", target_lang.to_source().into()),
+        ("entries", entries),
+        ("entries_format", match entries_format {
+            GlossaryEntriesFormat::Tsv => "tsv".to_string(),
+            GlossaryEntriesFormat::Csv => "csv".to_string(),
+        }),
+    ]
+}
 
 error_chain! {
     foreign_links {
@@ -446,18 +892,104 @@ error_chain! {
             description("The requested resource was not found.")
             display("The requested resource was not found.")
         }
+        /// The character quota of the account for the current billing period is used up (HTTP 456).
+        QuotaExceeded(status: u16, message: String) {
+            description("The translation quota has been exceeded.")
+            display("The translation quota has been exceeded: '{}'.", message)
+        }
+        /// Too many requests were sent in a short time (HTTP 429, or 529 from DeepL). If the server
+        /// sent a `Retry-After` header, it holds the time to wait before sending the next request.
+        RateLimited(status: u16, message: String, retry_after: Option<std::time::Duration>) {
+            description("Too many requests, please try again later.")
+            display("Too many requests, please try again later: '{}'.", message)
+        }
+        /// The request was rejected because it is too large (HTTP 413).
+        PayloadTooLarge(status: u16, message: String) {
+            description("The request is too large.")
+            display("The request is too large: '{}'.", message)
+        }
+        /// Glossaries are not supported for this combination of source and target language.
+        UnsupportedGlossaryLanguagePair(source_lang: String, target_lang: String) {
+            description("Glossaries are not supported for this language pair.")
+            display("Glossaries are not supported for the language pair '{}' -> '{}'.", source_lang, target_lang)
+        }
+        /// A language code could not be parsed, see [Language].
+        InvalidLanguageCode(code: String) {
+            description("The language code is invalid.")
+            display("The language code '{}' is invalid.", code)
+        }
+        /// A language without regional variant was used as target language, see [Language::to_target].
+        TargetLanguageWithoutVariant(code: String) {
+            description("The target language needs a regional variant.")
+            display("'{}' needs a regional variant like {} as target language.", code, match code.as_str() {
+                "PT" => "PT-BR or PT-PT",
+                _ => "EN-US or EN-GB",
+            })
+        }
+        /// The translation of a document failed on the server side.
+        DocumentTranslationError(message: String) {
+            description("The document translation failed.")
+            display("The document translation failed: '{}'.", message)
+        }
+        /// Cause of an error of a request that was retried according to the [RetryPolicy], holding
+        /// the total number of attempts. See [Error::attempts].
+        Attempts(attempts: u32) {
+            description("The request was retried.")
+            display("The request was sent {} times.", attempts)
+        }
+        /// The translation was not sent, as it would exceed the [CharacterBudget]. Holds the number
+        /// of characters of the translation and the remaining characters of the budget.
+        BudgetExceeded(characters: u64, remaining: u64) {
+            description("The translation would exceed the character budget.")
+            display("The translation of {} characters would exceed the character budget, {} characters remaining.", characters, remaining)
+        }
+        /// A request was not found in the [Cassette] that is replayed. Holds the method and path of the request.
+        UnmatchedRequest(request: String) {
+            description("No recorded interaction matches the request.")
+            display("No recorded interaction matches the request '{}'.", request)
+        }
+        /// A [Cassette] file could not be read.
+        CassetteError(message: String) {
+            description("The cassette is invalid.")
+            display("The cassette is invalid: {}.", message)
+        }
+        /// The client settings passed to the [DeepLBuilder] are invalid.
+        ConfigurationError(message: String) {
+            description("The client configuration is invalid.")
+            display("The client configuration is invalid: {}.", message)
+        }
     }
 
     skip_msg_variant
 }
 
+impl Error {
+    /// Number of attempts that were made for the failed request. It is greater than 1 if the request
+    /// was retried according to the [RetryPolicy], while the [kind](Error::kind) is still the one
+    /// of the last attempt.
+    pub fn attempts(&self) -> u32 {
+        let cause = self.1.next_error.as_ref().and_then(|e| e.downcast_ref::<Error>());
+        match cause.map(Error::kind) {
+            Some(ErrorKind::Attempts(attempts)) => *attempts,
+            _ => 1,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::mock::MockServer;
+    use std::sync::OnceLock;
+
+    /// Server shared by all tests of this module.
+    fn mock_server() -> &'static MockServer {
+        static SERVER: OnceLock<MockServer> = OnceLock::new();
+        SERVER.get_or_init(MockServer::start)
+    }
 
     fn create_deepl() -> DeepL {
-        let key = std::env::var("DEEPL_API_KEY").unwrap();
-        DeepL::new(key)
+        mock_server().deepl()
     }
 
     #[test]
@@ -466,16 +998,46 @@ mod tests {
         assert!(usage_information.character_limit > 0);
     }
 
+    #[test]
+    fn usage_information_fields() {
+        let usage_information: UsageInformation = serde_json::from_str(r#"{
+            "character_count": 180118,
+            "character_limit": 1250000,
+            "document_count": 5,
+            "document_limit": 10,
+            "api_key_character_count": 1000,
+            "api_key_character_limit": 0,
+            "start_time": "2025-05-13T09:18:42Z",
+            "end_time": "2025-06-13T09:18:42Z"
+        }"#).unwrap();
+        assert_eq!(usage_information.document_count, Some(5));
+        assert_eq!(usage_information.team_document_limit, None);
+        assert_eq!(usage_information.api_key_character_count, Some(1000));
+        assert_eq!(usage_information.end_time.unwrap().to_rfc3339(), "2025-06-13T09:18:42+00:00");
+        assert_eq!(usage_information.remaining_characters(), 1069882);
+        assert!((usage_information.percent_used() - 14.40944).abs() < 0.0001);
+
+        let usage_information: UsageInformation = serde_json::from_str(r#"{"character_count": 20, "character_limit": 10}"#).unwrap();
+        assert_eq!(usage_information.document_limit, None);
+        assert_eq!(usage_information.remaining_characters(), 0);
+        assert_eq!(usage_information.percent_used(), 200.0);
+    }
+
     #[test]
     fn source_languages() {
         let source_languages = create_deepl().source_languages().unwrap();
         assert_eq!(source_languages.last().unwrap().name, "Chinese");
+        assert_eq!(source_languages.last().unwrap().supports_formality, None);
     }
 
     #[test]
     fn target_languages() {
         let target_languages = create_deepl().target_languages().unwrap();
         assert_eq!(target_languages.last().unwrap().name, "Chinese (simplified)");
+        let german = target_languages.iter().find(|lang| lang.language == "DE").unwrap();
+        assert_eq!(german.supports_formality, Some(true));
+        let british = target_languages.iter().find(|lang| lang.language == "EN-GB").unwrap();
+        assert_eq!(british.supports_formality, Some(false));
     }
 
     #[test]
@@ -485,81 +1047,117 @@ mod tests {
             (
                 None,
                 TranslatableTextList {
-                    source_language: Some("DE".to_string()),
-                    target_language: "EN-US".to_string(),
+                    source_language: Some("DE".into()),
+                    target_language: "EN-US".into(),
                     texts: vec!["ja".to_string()],
                 },
                 vec![TranslatedText {
                     detected_source_language: "DE".to_string(),
                     text: "yes".to_string(),
+                    billed_characters: None,
+                    model_type_used: None,
                 }],
             ),
             (
                 Some(TranslationOptions {
-                    split_sentences: None,
                     preserve_formatting: Some(true),
-                    glossary_id: None,
-                    formality: None,
+                    ..Default::default()
                 }),
                 TranslatableTextList {
-                    source_language: Some("DE".to_string()),
-                    target_language: "EN-US".to_string(),
+                    source_language: Some("DE".into()),
+                    target_language: "EN-US".into(),
                     texts: vec!["ja\n nein".to_string()],
                 },
                 vec![TranslatedText {
                     detected_source_language: "DE".to_string(),
                     text: "yes\n no".to_string(),
+                    billed_characters: None,
+                    model_type_used: None,
                 }],
             ),
             (
                 Some(TranslationOptions {
                     split_sentences: Some(SplitSentences::None),
-                    preserve_formatting: None,
-                    glossary_id: None,
-                    formality: None,
+                    ..Default::default()
                 }),
                 TranslatableTextList {
-                    source_language: Some("DE".to_string()),
-                    target_language: "EN-US".to_string(),
+                    source_language: Some("DE".into()),
+                    target_language: "EN-US".into(),
                     texts: vec!["Ja. Nein.".to_string()],
                 },
                 vec![TranslatedText {
                     detected_source_language: "DE".to_string(),
                     text: "Yes. No.".to_string(),
+                    billed_characters: None,
+                    model_type_used: None,
                 }],
             ),
             (
                 Some(TranslationOptions {
-                    split_sentences: None,
-                    preserve_formatting: None,
-                    glossary_id: None,
                     formality: Some(Formality::More),
+                    ..Default::default()
                 }),
                 TranslatableTextList {
-                    source_language: Some("EN".to_string()),
-                    target_language: "DE".to_string(),
+                    source_language: Some("EN".into()),
+                    target_language: "DE".into(),
                     texts: vec!["Please go home.".to_string()],
                 },
                 vec![TranslatedText {
                     detected_source_language: "EN".to_string(),
                     text: "Bitte gehen Sie nach Hause.".to_string(),
+                    billed_characters: None,
+                    model_type_used: None,
                 }],
             ),
             (
                 Some(TranslationOptions {
-                    split_sentences: None,
-                    preserve_formatting: None,
-                    glossary_id: None,
                     formality: Some(Formality::Less),
+                    ..Default::default()
+                }),
+                TranslatableTextList {
+                    source_language: Some("EN".into()),
+                    target_language: "DE".into(),
+                    texts: vec!["Please go home.".to_string()],
+                },
+                vec![TranslatedText {
+                    detected_source_language: "EN".to_string(),
+                    text: "Bitte geh nach Hause.".to_string(),
+                    billed_characters: None,
+                    model_type_used: None,
+                }],
+            ),
+            (
+                Some(TranslationOptions {
+                    formality: Some(Formality::PreferLess),
+                    ..Default::default()
                 }),
                 TranslatableTextList {
-                    source_language: Some("EN".to_string()),
-                    target_language: "DE".to_string(),
+                    source_language: Some("EN".into()),
+                    target_language: "DE".into(),
                     texts: vec!["Please go home.".to_string()],
                 },
                 vec![TranslatedText {
                     detected_source_language: "EN".to_string(),
                     text: "Bitte geh nach Hause.".to_string(),
+                    billed_characters: None,
+                    model_type_used: None,
+                }],
+            ),
+            (
+                Some(TranslationOptions {
+                    formality: Some(Formality::PreferMore),
+                    ..Default::default()
+                }),
+                TranslatableTextList {
+                    source_language: Some("DE".into()),
+                    target_language: "EN-US".into(),
+                    texts: vec!["ja".to_string()],
+                },
+                vec![TranslatedText {
+                    detected_source_language: "DE".to_string(),
+                    text: "yes".to_string(),
+                    billed_characters: None,
+                    model_type_used: None,
                 }],
             ),
         ];
@@ -572,19 +1170,59 @@ mod tests {
     #[should_panic(expected = "Error(ServerError(\"Parameter 'text' not specified.")]
     fn translate_empty() {
         let texts = TranslatableTextList {
-            source_language: Some("DE".to_string()),
-            target_language: "EN-US".to_string(),
+            source_language: Some("DE".into()),
+            target_language: "EN-US".into(),
             texts: vec![],
         };
         create_deepl().translate(None, texts).unwrap();
     }
 
+    #[test]
+    fn translate_batches() {
+        let deepl = mock_server().builder().parallel_requests(2).build().unwrap();
+        let texts: Vec<String> = (0..120).map(|i| if i % 2 == 0 { "ja" } else { "nein" }.to_string()).collect();
+        let translated = deepl.translate(None, TranslatableTextList {
+            source_language: Some("DE".into()),
+            target_language: "EN-US".into(),
+            texts,
+        }).unwrap();
+        assert_eq!(translated.len(), 120);
+        assert_eq!(translated[0].text, "yes");
+        assert_eq!(translated[119].text, "no");
+    }
+
     #[test]
     #[should_panic(expected = "Error(ServerError(\"Value for 'target_lang' not supported.")]
     fn translate_wrong_language() {
         let texts = TranslatableTextList {
             source_language: None,
-            target_language: "NONEXISTING".to_string(),
+            target_language: "NONEXISTING".into(),
+            texts: vec!["ja".to_string()],
+        };
+        create_deepl().translate(None, texts).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(ServerError(\"'formality' is not supported for given 'target_lang'.")]
+    fn translate_formality_unsupported() {
+        let options = TranslationOptions {
+            formality: Some(Formality::More),
+            ..Default::default()
+        };
+        let texts = TranslatableTextList {
+            source_language: Some("DE".into()),
+            target_language: "EN-US".into(),
+            texts: vec!["ja".to_string()],
+        };
+        create_deepl().translate(Some(options), texts).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(TargetLanguageWithoutVariant(\"EN\")")]
+    fn translate_target_without_variant() {
+        let texts = TranslatableTextList {
+            source_language: None,
+            target_language: "en".into(),
             texts: vec!["ja".to_string()],
         };
         create_deepl().translate(None, texts).unwrap();
@@ -595,11 +1233,11 @@ mod tests {
     fn translate_unauthorized() {
         let key = "wrong_key".to_string();
         let texts = TranslatableTextList {
-            source_language: Some("DE".to_string()),
-            target_language: "EN-US".to_string(),
+            source_language: Some("DE".into()),
+            target_language: "EN-US".into(),
             texts: vec!["ja".to_string()],
         };
-        DeepL::new(key).translate(None, texts).unwrap();
+        DeepL::builder(key).server_url(mock_server().url()).build().unwrap().translate(None, texts).unwrap();
     }
 
     #[test]
@@ -609,8 +1247,8 @@ mod tests {
 
         let mut glossary = deepl.create_glossary(
             glossary_name.clone(),
-            "en".to_string(),
-            "de".to_string(),
+            "en".into(),
+            "de".into(),
             "Action,Handlung".to_string(),
             GlossaryEntriesFormat::Csv
         ).unwrap();
@@ -622,6 +1260,9 @@ mod tests {
         assert_eq!(glossary.name, glossary_name);
         assert_eq!(glossary.entry_count, 1);
 
+        let entries = deepl.get_glossary_entries(glossary.glossary_id.clone()).unwrap();
+        assert_eq!(entries, vec![("Action".to_string(), "Handlung".to_string())]);
+
         let mut glossaries = deepl.list_glossaries().unwrap().glossaries;
         glossaries.retain(|glossary| glossary.name == glossary_name);
         let glossary = glossaries.pop().unwrap();
@@ -631,15 +1272,13 @@ mod tests {
         assert_eq!(deepl.translate(
             Some(
                 TranslationOptions {
-                    split_sentences: None,
-                    preserve_formatting: None,
                     glossary_id: Some(glossary.glossary_id.clone()),
-                    formality: None,
+                    ..Default::default()
                 }
             ),
             TranslatableTextList {
-                source_language: Some("en".to_string()),
-                target_language: "de".to_string(),
+                source_language: Some("en".into()),
+                target_language: "de".into(),
                 texts: vec!["Action".to_string()],
             }
         ).unwrap().pop().unwrap().text, "Handlung");
@@ -648,4 +1287,188 @@ mod tests {
         let glossary_response = deepl.get_glossary(glossary.glossary_id);
         assert_eq!(glossary_response.unwrap_err().to_string(), crate::ErrorKind::NotFoundError.to_string());
     }
+
+    #[test]
+    fn glossary_language_pairs() {
+        let pairs = create_deepl().glossary_language_pairs().unwrap();
+        assert!(pairs.contains(&GlossaryLanguagePair { source_lang: "en".into(), target_lang: "de".into() }));
+
+        let deepl = mock_server().builder().validate_glossary_languages(true).build().unwrap();
+        let result = deepl.create_glossary(
+            "test_glossary_unsupported".to_string(),
+            "en".into(),
+            "xx".into(),
+            "Action,Handlung".to_string(),
+            GlossaryEntriesFormat::Csv
+        );
+        assert_eq!(result.unwrap_err().to_string(), "Glossaries are not supported for the language pair 'EN' -> 'XX'.");
+    }
+
+    #[test]
+    fn check_glossary_language_pair() {
+        let pairs = vec![GlossaryLanguagePair { source_lang: "en".into(), target_lang: "de".into() }];
+        assert!(crate::check_glossary_language_pair(&pairs, &"EN".into(), &"de".into()).is_ok());
+        assert!(crate::check_glossary_language_pair(&pairs, &"en-GB".into(), &"de".into()).is_ok());
+        let error = crate::check_glossary_language_pair(&pairs, &"de".into(), &"en-us".into()).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::UnsupportedGlossaryLanguagePair(source, target) if source == "DE" && target == "EN"));
+    }
+
+    #[test]
+    fn translate_xml() {
+        let options = TranslationOptions {
+            tag_handling: Some(TagHandling::Xml),
+            outline_detection: Some(false),
+            splitting_tags: Some(vec!["p".to_string()]),
+            ignore_tags: Some(vec!["x".to_string(), "y".to_string()]),
+            ..Default::default()
+        };
+        let texts = || TranslatableTextList {
+            source_language: Some("DE".into()),
+            target_language: "EN-US".into(),
+            texts: vec!["<p>ja</p><x>nein</x>".to_string()],
+        };
+        assert_eq!(translation_params(Some(options.clone()), texts()).unwrap(), vec![
+            ("target_lang", "EN-US".to_string()),
+            ("source_lang", "DE".to_string()),
+            ("text", "<p>ja</p><x>nein</x>".to_string()),
+            ("tag_handling", "xml".to_string()),
+            ("outline_detection", "0".to_string()),
+            ("splitting_tags", "p".to_string()),
+            ("ignore_tags", "x,y".to_string()),
+        ]);
+
+        let translated = create_deepl().translate(Some(options), texts()).unwrap();
+        assert_eq!(translated[0].text, "<p>yes</p><x>nein</x>");
+    }
+
+    #[test]
+    fn translate_context() {
+        let options = TranslationOptions {
+            context: Some("Label of a button that saves the document.".to_string()),
+            ..Default::default()
+        };
+        let texts = || TranslatableTextList {
+            source_language: Some("EN".into()),
+            target_language: "DE".into(),
+            texts: vec!["Save".to_string()],
+        };
+        assert_eq!(translation_params(Some(options.clone()), texts()).unwrap().last().unwrap(),
+            &("context", "Label of a button that saves the document.".to_string()));
+
+        let translated = create_deepl().translate(Some(options), texts()).unwrap();
+        assert_eq!(translated[0].text, "Speichern");
+    }
+
+    #[test]
+    fn translate_model_type() {
+        let options = TranslationOptions {
+            model_type: Some(ModelType::PreferQualityOptimized),
+            show_billed_characters: Some(true),
+            ..Default::default()
+        };
+        let texts = TranslatableTextList {
+            source_language: Some("DE".into()),
+            target_language: "EN-US".into(),
+            texts: vec!["ja".to_string()],
+        };
+        assert_eq!(create_deepl().translate(Some(options), texts).unwrap(), vec![TranslatedText {
+            detected_source_language: "DE".to_string(),
+            text: "yes".to_string(),
+            billed_characters: Some(2),
+            model_type_used: Some("quality_optimized".to_string()),
+        }]);
+    }
+
+    #[test]
+    fn parse_glossary_entries() {
+        let entries = crate::parse_glossary_entries("Action\tHandlung\nGo home\tGeh nach Hause\n").unwrap();
+        assert_eq!(entries, vec![
+            ("Action".to_string(), "Handlung".to_string()),
+            ("Go home".to_string(), "Geh nach Hause".to_string()),
+        ]);
+        assert!(crate::parse_glossary_entries("").unwrap().is_empty());
+        assert!(crate::parse_glossary_entries("Action").is_err());
+    }
+
+    #[test]
+    fn error_for_status() {
+        let mut headers = reqwest::header::HeaderMap::new();
+        let message = || Some(ServerErrorMessage { message: "Message".to_string(), detail: Some("Detail".to_string()) });
+        let error = |status| crate::error_for_status(reqwest::StatusCode::from_u16(status).unwrap(), &headers, message());
+
+        assert!(matches!(error(403), ErrorKind::AuthorizationError));
+        assert!(matches!(error(404), ErrorKind::NotFoundError));
+        assert!(matches!(error(413), ErrorKind::PayloadTooLarge(413, m) if m == "Message: Detail"));
+        assert!(matches!(error(456), ErrorKind::QuotaExceeded(456, m) if m == "Message: Detail"));
+        assert!(matches!(error(529), ErrorKind::RateLimited(529, _, None)));
+        assert!(matches!(error(500), ErrorKind::ServerError(m) if m == "Message: Detail"));
+
+        headers.insert(reqwest::header::RETRY_AFTER, "3".parse().unwrap());
+        let error = crate::error_for_status(reqwest::StatusCode::TOO_MANY_REQUESTS, &headers, None);
+        assert!(matches!(error, ErrorKind::RateLimited(429, m, Some(d)) if m == "429 Too Many Requests" && d.as_secs() == 3));
+    }
+
+    #[test]
+    fn retries() {
+        let server = MockServer::start();
+        let deepl = server.builder()
+            .retry_policy(RetryPolicy { max_retries: 1, ..RetryPolicy::default() })
+            .build()
+            .unwrap();
+
+        server.inject_error(429, "Too many requests");
+        server.inject_error(429, "Too many requests");
+        let error = deepl.usage_information().unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::RateLimited(429, ..)));
+        assert_eq!(error.attempts(), 2);
+
+        // Errors that are not retried keep their kind as well.
+        server.inject_error(429, "Too many requests");
+        server.inject_error(400, "Bad request");
+        let error = deepl.usage_information().unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::ServerError(message) if message == "Bad request: "));
+        assert_eq!(error.attempts(), 2);
+
+        server.inject_error(400, "Bad request");
+        assert_eq!(deepl.usage_information().unwrap_err().attempts(), 1);
+
+        // Failures without a response, here a retry that is missing in the cassette, count as well.
+        let temp_dir = assert_fs::TempDir::new().unwrap();
+        let path = temp_dir.path().join("cassette.json");
+        server.inject_error(429, "Too many requests");
+        let recording = server.builder().cassette(Cassette::record(&path)).build().unwrap();
+        assert!(recording.usage_information().is_err());
+        let replaying = server.builder()
+            .retry_policy(RetryPolicy { max_retries: 1, ..RetryPolicy::default() })
+            .cassette(Cassette::replay(&path).unwrap())
+            .build()
+            .unwrap();
+        let error = replaying.usage_information().unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::UnmatchedRequest(_)));
+        assert_eq!(error.attempts(), 2);
+    }
+
+    #[test]
+    fn builder() {
+        assert!(DeepL::builder("key".to_string())
+            .server_url("http://localhost:3000/")
+            .timeout(std::time::Duration::from_secs(5))
+            .user_agent("deepl-api-tests")
+            .proxy("http://localhost:8080")
+            .build()
+            .is_ok());
+
+        let deepl = DeepL::builder("key".to_string()).server_url("not a url").build();
+        assert!(matches!(deepl.err().unwrap().kind(), ErrorKind::ConfigurationError(_)));
+
+        let deepl = DeepL::builder("key".to_string()).proxy("::").build();
+        assert!(matches!(deepl.err().unwrap().kind(), ErrorKind::ConfigurationError(_)));
+
+        // A custom client replaces the client settings of the builder.
+        assert!(DeepL::builder("key".to_string())
+            .proxy("::")
+            .client(reqwest::blocking::Client::new())
+            .build()
+            .is_ok());
+    }
 }
@@ -0,0 +1,935 @@
+//! A local stand-in for the DeepL API, available with the `mock` cargo feature.
+//!
+//! The [MockServer] implements the text translation, usage, language, glossary (v2 and v3) and
+//! document endpoints, so that code using this crate can be tested offline and without an API key.
+//! Its translations are fake, but deterministic: a small built-in dictionary translates a few
+//! German and English words, all other words are returned unchanged.
+//!
+//! # Example
+//!
+//! ```rust
+//! use deepl_api::*;
+//! use deepl_api::mock::MockServer;
+//!
+//! let server = MockServer::start();
+//! let deepl = server.deepl();
+//!
+//! let texts = TranslatableTextList {
+//!     source_language: Some("DE".into()),
+//!     target_language: "EN-US".into(),
+//!     texts: vec!["Ja, danke!".to_string()],
+//! };
+//! assert_eq!(deepl.translate(None, texts).unwrap()[0].text, "Yes, thanks!");
+//!
+//! server.inject_error(503, "Service unavailable");
+//! assert!(deepl.usage_information().is_err());
+//! ```
+
+use crate::{DeepL, DeepLBuilder};
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+/// Maximum number of texts per translation request, like the real API.
+const MAX_TEXTS: usize = 50;
+
+/// Maximum size of a request body in bytes, like the real API.
+const MAX_BODY_SIZE: usize = 128 * 1024;
+
+const SOURCE_LANGUAGES: [(&str, &str); 15] = [
+    ("BG", "Bulgarian"), ("CS", "Czech"), ("DA", "Danish"), ("DE", "German"), ("EL", "Greek"),
+    ("EN", "English"), ("ES", "Spanish"), ("FR", "French"), ("IT", "Italian"), ("JA", "Japanese"),
+    ("NL", "Dutch"), ("PL", "Polish"), ("PT", "Portuguese"), ("RU", "Russian"), ("ZH", "Chinese"),
+];
+
+const TARGET_LANGUAGES: [(&str, &str, bool); 13] = [
+    ("DE", "German", true), ("EN-GB", "English (British)", false), ("EN-US", "English (American)", false),
+    ("ES", "Spanish", true), ("FR", "French", true), ("IT", "Italian", true), ("JA", "Japanese", true),
+    ("NL", "Dutch", true), ("PL", "Polish", true), ("PT-BR", "Portuguese (Brazilian)", true),
+    ("PT-PT", "Portuguese (European)", true), ("RU", "Russian", true), ("ZH", "Chinese (simplified)", false),
+];
+
+const GERMAN_TO_ENGLISH: [(&str, &str); 9] = [
+    ("ja", "yes"), ("nein", "no"), ("hallo", "hello"), ("welt", "world"), ("bitte", "please"),
+    ("danke", "thanks"), ("haus", "house"), ("speichern", "save"), ("handlung", "plot"),
+];
+
+const ENGLISH_TO_GERMAN: [(&str, &str); 9] = [
+    ("yes", "ja"), ("no", "nein"), ("hello", "hallo"), ("world", "welt"), ("please", "bitte"),
+    ("thanks", "danke"), ("house", "haus"), ("save", "speichern"), ("action", "aktion"),
+];
+
+/// A local HTTP server that behaves like the DeepL API. It runs in a background thread and is shut
+/// down when dropped.
+///
+/// Only requests with [API_KEY](MockServer::API_KEY) are accepted, all others fail with HTTP 403.
+/// The character quota can be limited via [set_character_limit](MockServer::set_character_limit),
+/// and arbitrary errors can be injected via [inject_error](MockServer::inject_error).
+pub struct MockServer {
+    url: String,
+    state: Arc<Mutex<MockState>>,
+    server: Arc<tiny_http::Server>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl MockServer {
+    /// The API key that is accepted by the mock server.
+    pub const API_KEY: &'static str = "mock-api-key";
+
+    /// Start a server on a random local port.
+    pub fn start() -> MockServer {
+        let server = Arc::new(tiny_http::Server::http("127.0.0.1:0").expect("cannot start mock server"));
+        let port = server.server_addr().to_ip().expect("mock server has no IP address").port();
+        let state = Arc::new(Mutex::new(MockState::default()));
+        let thread = {
+            let (server, state) = (server.clone(), state.clone());
+            std::thread::spawn(move || {
+                for request in server.incoming_requests() {
+                    let _ = handle(&state, request);
+                }
+            })
+        };
+        MockServer {
+            url: format!("http://127.0.0.1:{}", port),
+            state,
+            server,
+            thread: Some(thread),
+        }
+    }
+
+    /// URL of the server, to be passed to [DeepLBuilder::server_url] or the `DEEPL_SERVER_URL`
+    /// environment variable of the `deepl` CLI.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// A [DeepLBuilder] that is already configured for this server.
+    pub fn builder(&self) -> DeepLBuilder {
+        DeepL::builder(MockServer::API_KEY.to_string()).server_url(&self.url)
+    }
+
+    /// A [DeepL] instance that uses this server.
+    pub fn deepl(&self) -> DeepL {
+        self.builder().build().expect("invalid mock server URL")
+    }
+
+    /// Limit the number of characters that can be translated. Further translations fail with HTTP 456.
+    pub fn set_character_limit(&self, character_limit: u64) {
+        self.state.lock().unwrap().character_limit = character_limit;
+    }
+
+    /// Number of characters translated so far.
+    pub fn character_count(&self) -> u64 {
+        self.state.lock().unwrap().character_count
+    }
+
+    /// Number of requests received so far.
+    pub fn request_count(&self) -> u64 {
+        self.state.lock().unwrap().request_count
+    }
+
+    /// Let the next request fail with this HTTP status and error message. Can be called several times
+    /// to let several consecutive requests fail. Rate limiting errors (429, 529) and 503 come with a
+    /// `Retry-After: 0` header, so that retries do not slow down tests.
+    pub fn inject_error(&self, status: u16, message: &str) {
+        self.state.lock().unwrap().errors.push_back((status, message.to_string()));
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.server.unblock();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+struct MockState {
+    character_count: u64,
+    character_limit: u64,
+    document_count: u64,
+    request_count: u64,
+    errors: VecDeque<(u16, String)>,
+    glossaries: Vec<MockGlossary>,
+    documents: HashMap<String, MockDocument>,
+}
+
+impl Default for MockState {
+    fn default() -> MockState {
+        MockState {
+            character_count: 0,
+            character_limit: 500_000,
+            document_count: 0,
+            request_count: 0,
+            errors: VecDeque::new(),
+            glossaries: vec![],
+            documents: HashMap::new(),
+        }
+    }
+}
+
+struct MockGlossary {
+    id: String,
+    name: String,
+    creation_time: String,
+    dictionaries: Vec<MockDictionary>,
+}
+
+struct MockDictionary {
+    source_lang: String,
+    target_lang: String,
+    entries: Vec<(String, String)>,
+}
+
+struct MockDocument {
+    key: String,
+    billed_characters: u64,
+    /// Number of status requests so far; the translation is done after the second one.
+    polls: u32,
+    content: Option<Vec<u8>>,
+}
+
+impl MockGlossary {
+    fn v2_json(&self) -> Value {
+        let dictionary = &self.dictionaries[0];
+        json!({
+            "glossary_id": self.id,
+            "name": self.name,
+            "ready": true,
+            "source_lang": dictionary.source_lang,
+            "target_lang": dictionary.target_lang,
+            "creation_time": self.creation_time,
+            "entry_count": dictionary.entries.len(),
+        })
+    }
+
+    fn v3_json(&self) -> Value {
+        json!({
+            "glossary_id": self.id,
+            "name": self.name,
+            "dictionaries": self.dictionaries.iter().map(MockDictionary::info_json).collect::<Vec<_>>(),
+            "creation_time": self.creation_time,
+        })
+    }
+
+    fn dictionary(&self, source_lang: &str, target_lang: &str) -> Option<&MockDictionary> {
+        self.dictionaries.iter().find(|dictionary| dictionary.is_pair(source_lang, target_lang))
+    }
+
+    /// Add a dictionary, replacing or merging with the existing one for the same language pair.
+    fn put_dictionary(&mut self, dictionary: MockDictionary, merge: bool) {
+        match self.dictionaries.iter_mut().find(|d| d.is_pair(&dictionary.source_lang, &dictionary.target_lang)) {
+            Some(existing) if merge => {
+                for (source, target) in dictionary.entries {
+                    existing.entries.retain(|(s, _)| *s != source);
+                    existing.entries.push((source, target));
+                }
+            }
+            Some(existing) => *existing = dictionary,
+            None => self.dictionaries.push(dictionary),
+        }
+    }
+}
+
+impl MockDictionary {
+    fn is_pair(&self, source_lang: &str, target_lang: &str) -> bool {
+        self.source_lang.eq_ignore_ascii_case(source_lang) && self.target_lang.eq_ignore_ascii_case(target_lang)
+    }
+
+    fn info_json(&self) -> Value {
+        json!({"source_lang": self.source_lang, "target_lang": self.target_lang, "entry_count": self.entries.len()})
+    }
+
+    fn tsv(&self) -> String {
+        self.entries.iter().map(|(source, target)| format!("{}\t{}\n", source, target)).collect()
+    }
+
+    /// Parse a dictionary from the JSON representation of the v3 API.
+    fn from_json(value: &Value) -> Option<MockDictionary> {
+        Some(MockDictionary {
+            source_lang: value["source_lang"].as_str()?.to_lowercase(),
+            target_lang: value["target_lang"].as_str()?.to_lowercase(),
+            entries: parse_entries(value["entries"].as_str()?, value["entries_format"].as_str()?)?,
+        })
+    }
+}
+
+/// A parsed request.
+struct MockRequest {
+    method: String,
+    path: String,
+    authorized: bool,
+    body_size: usize,
+    params: Vec<(String, String)>,
+    json: Value,
+    file: Option<Vec<u8>>,
+}
+
+impl MockRequest {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params.iter().find(|(n, _)| n == name).map(|(_, value)| value.as_str())
+    }
+
+    fn parse(request: &mut tiny_http::Request) -> std::io::Result<MockRequest> {
+        let header = |name: &str| {
+            request
+                .headers()
+                .iter()
+                .find(|header| header.field.as_str().as_str().eq_ignore_ascii_case(name))
+                .map(|header| header.value.as_str().to_string())
+                .unwrap_or_default()
+        };
+        let authorized = header("Authorization") == format!("DeepL-Auth-Key {}", MockServer::API_KEY);
+        let content_type = header("Content-Type");
+        let (path, query) = request.url().split_once('?').unwrap_or((request.url(), ""));
+        let mut parsed = MockRequest {
+            method: request.method().to_string(),
+            path: path.to_string(),
+            authorized,
+            body_size: 0,
+            params: parse_form(query.as_bytes()),
+            json: Value::Null,
+            file: None,
+        };
+
+        let mut body = vec![];
+        request.as_reader().read_to_end(&mut body)?;
+        parsed.body_size = body.len();
+        if content_type.starts_with("application/x-www-form-urlencoded") {
+            parsed.params.extend(parse_form(&body));
+        } else if content_type.starts_with("application/json") {
+            parsed.json = serde_json::from_slice(&body).unwrap_or_default();
+        } else if let Some((_, boundary)) = content_type.split_once("boundary=") {
+            for (name, content) in parse_multipart(&body, boundary) {
+                match name.as_str() {
+                    "file" => parsed.file = Some(content),
+                    _ => parsed.params.push((name, String::from_utf8_lossy(&content).to_string())),
+                }
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+/// A response to be sent.
+struct MockResponse {
+    status: u16,
+    content_type: &'static str,
+    body: Vec<u8>,
+}
+
+impl MockResponse {
+    fn json(value: Value) -> MockResponse {
+        MockResponse { status: 200, content_type: "application/json", body: value.to_string().into_bytes() }
+    }
+
+    fn error(status: u16, message: &str) -> MockResponse {
+        MockResponse { status, ..MockResponse::json(json!({ "message": message })) }
+    }
+
+    fn no_content() -> MockResponse {
+        MockResponse { status: 204, content_type: "text/plain", body: vec![] }
+    }
+
+    fn data(content_type: &'static str, body: Vec<u8>) -> MockResponse {
+        MockResponse { status: 200, content_type, body }
+    }
+}
+
+/// Private helper that answers a single request.
+fn handle(state: &Mutex<MockState>, mut request: tiny_http::Request) -> std::io::Result<()> {
+    let parsed = MockRequest::parse(&mut request)?;
+    let response = {
+        let mut state = state.lock().unwrap();
+        state.request_count += 1;
+        match state.errors.pop_front() {
+            Some((status, message)) => MockResponse::error(status, &message),
+            None => route(&mut state, &parsed),
+        }
+    };
+
+    let mut http_response = tiny_http::Response::from_data(response.body)
+        .with_status_code(response.status)
+        .with_header(tiny_http::Header::from_bytes("Content-Type", response.content_type).unwrap());
+    if matches!(response.status, 429 | 503 | 529) {
+        http_response.add_header(tiny_http::Header::from_bytes("Retry-After", "0").unwrap());
+    }
+    request.respond(http_response)
+}
+
+/// Private helper that dispatches a request to the matching endpoint.
+fn route(state: &mut MockState, request: &MockRequest) -> MockResponse {
+    if !request.authorized {
+        return MockResponse::error(403, "Authorization failure, check auth_key");
+    }
+    if request.body_size > MAX_BODY_SIZE {
+        return MockResponse::error(413, "Request Entity Too Large");
+    }
+    let segments: Vec<&str> = request.path.trim_matches('/').split('/').collect();
+    match (request.method.as_str(), segments.as_slice()) {
+        (_, ["v2", "usage"]) => MockResponse::json(json!({
+            "character_count": state.character_count,
+            "character_limit": state.character_limit,
+            "document_count": state.document_count,
+            "document_limit": 1000,
+        })),
+        (_, ["v2", "languages"]) => languages(request.param("type").unwrap_or("source")),
+        ("POST", ["v2", "translate"]) => translate(state, request),
+        ("GET", ["v2", "glossary-language-pairs"]) => glossary_language_pairs(),
+        ("POST", ["v2", "glossaries"]) => create_glossary(state, request),
+        ("GET", ["v2", "glossaries"]) => MockResponse::json(json!({
+            "glossaries": state.glossaries.iter().filter(|g| g.dictionaries.len() == 1).map(MockGlossary::v2_json).collect::<Vec<_>>(),
+        })),
+        ("GET", ["v2", "glossaries", id]) => match find_glossary(state, id) {
+            Some(glossary) if glossary.dictionaries.len() == 1 => MockResponse::json(glossary.v2_json()),
+            _ => MockResponse::error(404, "Not found"),
+        },
+        ("GET", ["v2", "glossaries", id, "entries"]) => match find_glossary(state, id) {
+            Some(glossary) if glossary.dictionaries.len() == 1 => {
+                MockResponse::data("text/tab-separated-values", glossary.dictionaries[0].tsv().into_bytes())
+            }
+            _ => MockResponse::error(404, "Not found"),
+        },
+        ("DELETE", ["v2" | "v3", "glossaries", id]) => {
+            let count = state.glossaries.len();
+            state.glossaries.retain(|glossary| glossary.id != *id);
+            match state.glossaries.len() < count {
+                true => MockResponse::no_content(),
+                false => MockResponse::error(404, "Not found"),
+            }
+        }
+        ("POST", ["v2", "document"]) => upload_document(state, request),
+        ("POST", ["v2", "document", id]) => match find_document(state, request, id) {
+            Ok(document) => {
+                document.polls += 1;
+                MockResponse::json(match document.polls {
+                    1 => json!({"document_id": id, "status": "queued"}),
+                    2 => json!({"document_id": id, "status": "translating", "seconds_remaining": 1}),
+                    _ => json!({"document_id": id, "status": "done", "billed_characters": document.billed_characters}),
+                })
+            }
+            Err(response) => response,
+        },
+        ("POST", ["v2", "document", id, "result"]) => match find_document(state, request, id) {
+            Ok(document) => match document.content.take() {
+                Some(content) => MockResponse::data("application/octet-stream", content),
+                None => MockResponse::error(404, "Document already downloaded"),
+            },
+            Err(response) => response,
+        },
+        ("POST", ["v3", "glossaries"]) => create_multilingual_glossary(state, request),
+        ("GET", ["v3", "glossaries"]) => MockResponse::json(json!({
+            "glossaries": state.glossaries.iter().map(MockGlossary::v3_json).collect::<Vec<_>>(),
+        })),
+        ("GET", ["v3", "glossaries", id]) => match find_glossary(state, id) {
+            Some(glossary) => MockResponse::json(glossary.v3_json()),
+            None => MockResponse::error(404, "Not found"),
+        },
+        ("PATCH", ["v3", "glossaries", id]) => update_multilingual_glossary(state, request, id),
+        ("PUT", ["v3", "glossaries", id, "dictionaries"]) => {
+            let Some(dictionary) = MockDictionary::from_json(&request.json) else {
+                return MockResponse::error(400, "Invalid dictionary");
+            };
+            let Some(glossary) = state.glossaries.iter_mut().find(|glossary| glossary.id == *id) else {
+                return MockResponse::error(404, "Not found");
+            };
+            let info = dictionary.info_json();
+            glossary.put_dictionary(dictionary, false);
+            MockResponse::json(info)
+        }
+        ("DELETE", ["v3", "glossaries", id, "dictionaries"]) => {
+            let (source_lang, target_lang) = (request.param("source_lang").unwrap_or_default(), request.param("target_lang").unwrap_or_default());
+            match state.glossaries.iter_mut().find(|glossary| glossary.id == *id) {
+                Some(glossary) if glossary.dictionary(source_lang, target_lang).is_some() => {
+                    glossary.dictionaries.retain(|dictionary| !dictionary.is_pair(source_lang, target_lang));
+                    MockResponse::no_content()
+                }
+                _ => MockResponse::error(404, "Not found"),
+            }
+        }
+        ("GET", ["v3", "glossaries", id, "entries"]) => {
+            let (source_lang, target_lang) = (request.param("source_lang").unwrap_or_default(), request.param("target_lang").unwrap_or_default());
+            match find_glossary(state, id).and_then(|glossary| glossary.dictionary(source_lang, target_lang)) {
+                Some(dictionary) => MockResponse::json(json!({"dictionaries": [{
+                    "source_lang": dictionary.source_lang,
+                    "target_lang": dictionary.target_lang,
+                    "entries": dictionary.tsv(),
+                    "entries_format": "tsv",
+                }]})),
+                None => MockResponse::error(404, "Not found"),
+            }
+        }
+        _ => MockResponse::error(404, "Not found"),
+    }
+}
+
+fn languages(language_type: &str) -> MockResponse {
+    match language_type {
+        "target" => MockResponse::json(Value::Array(TARGET_LANGUAGES.iter().map(|(language, name, supports_formality)| {
+            json!({"language": language, "name": name, "supports_formality": supports_formality})
+        }).collect())),
+        _ => MockResponse::json(Value::Array(SOURCE_LANGUAGES.iter().map(|(language, name)| {
+            json!({"language": language, "name": name})
+        }).collect())),
+    }
+}
+
+fn glossary_language_pairs() -> MockResponse {
+    let languages = ["de", "en", "es", "fr", "it", "ja", "nl", "pl", "pt", "ru", "zh"];
+    let pairs: Vec<Value> = languages.iter()
+        .flat_map(|source| languages.iter().filter(move |target| *target != source).map(move |target| {
+            json!({"source_lang": source, "target_lang": target})
+        }))
+        .collect();
+    MockResponse::json(json!({ "supported_languages": pairs }))
+}
+
+fn is_glossary_language(language: &str) -> bool {
+    ["de", "en", "es", "fr", "it", "ja", "nl", "pl", "pt", "ru", "zh"].iter().any(|l| l.eq_ignore_ascii_case(language))
+}
+
+fn find_glossary<'a>(state: &'a MockState, id: &str) -> Option<&'a MockGlossary> {
+    state.glossaries.iter().find(|glossary| glossary.id == id)
+}
+
+fn find_document<'a>(state: &'a mut MockState, request: &MockRequest, id: &str) -> Result<&'a mut MockDocument, MockResponse> {
+    match state.documents.get_mut(id) {
+        Some(document) if Some(document.key.as_str()) == request.param("document_key") => Ok(document),
+        Some(_) => Err(MockResponse::error(403, "Invalid document key")),
+        None => Err(MockResponse::error(404, "Document not found")),
+    }
+}
+
+fn translate(state: &mut MockState, request: &MockRequest) -> MockResponse {
+    let texts: Vec<&str> = request.params.iter().filter(|(name, _)| name == "text").map(|(_, text)| text.as_str()).collect();
+    if texts.is_empty() {
+        return MockResponse::error(400, "Parameter 'text' not specified.");
+    }
+    if texts.len() > MAX_TEXTS {
+        return MockResponse::error(400, "Too many texts.");
+    }
+    let characters: u64 = texts.iter().map(|text| text.chars().count() as u64).sum();
+    let translator = match Translation::new(state, request, characters) {
+        Ok(translator) => translator,
+        Err(response) => return response,
+    };
+
+    let translations: Vec<Value> = texts.iter().map(|text| {
+        let (detected_source_language, translated) = translator.translate(text);
+        let mut translation = json!({"detected_source_language": detected_source_language, "text": translated});
+        if request.param("show_billed_characters") == Some("1") {
+            translation["billed_characters"] = json!(text.chars().count());
+        }
+        if let Some(model_type) = request.param("model_type") {
+            translation["model_type_used"] = json!(match model_type {
+                "latency_optimized" => "latency_optimized",
+                _ => "quality_optimized",
+            });
+        }
+        translation
+    }).collect();
+    MockResponse::json(json!({ "translations": translations }))
+}
+
+fn upload_document(state: &mut MockState, request: &MockRequest) -> MockResponse {
+    let Some(file) = &request.file else {
+        return MockResponse::error(400, "Parameter 'file' not specified.");
+    };
+    let text = String::from_utf8_lossy(file).to_string();
+    let characters = text.chars().count() as u64;
+    let translator = match Translation::new(state, request, characters) {
+        Ok(translator) => translator,
+        Err(response) => return response,
+    };
+    let (_, translated) = translator.translate(&text);
+
+    let id = random_hex(16).to_uppercase();
+    let key = random_hex(32).to_uppercase();
+    state.document_count += 1;
+    state.documents.insert(id.clone(), MockDocument {
+        key: key.clone(),
+        billed_characters: characters.max(50_000),
+        polls: 0,
+        content: Some(translated.into_bytes()),
+    });
+    MockResponse::json(json!({"document_id": id, "document_key": key}))
+}
+
+fn create_glossary(state: &mut MockState, request: &MockRequest) -> MockResponse {
+    let param = |name| request.param(name).unwrap_or_default();
+    if !is_glossary_language(param("source_lang")) || !is_glossary_language(param("target_lang")) {
+        return MockResponse::error(400, "Unsupported glossary source and target language pair.");
+    }
+    let Some(entries) = parse_entries(param("entries"), param("entries_format")) else {
+        return MockResponse::error(400, "Invalid glossary entries provided.");
+    };
+    let glossary = MockGlossary {
+        id: random_hex(16),
+        name: param("name").to_string(),
+        creation_time: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+        dictionaries: vec![MockDictionary {
+            source_lang: param("source_lang").to_lowercase(),
+            target_lang: param("target_lang").to_lowercase(),
+            entries,
+        }],
+    };
+    let response = MockResponse::json(glossary.v2_json());
+    state.glossaries.push(glossary);
+    response
+}
+
+fn create_multilingual_glossary(state: &mut MockState, request: &MockRequest) -> MockResponse {
+    let dictionaries: Option<Vec<MockDictionary>> = request.json["dictionaries"]
+        .as_array()
+        .map(|dictionaries| dictionaries.iter().map(MockDictionary::from_json).collect())
+        .unwrap_or_default();
+    let (Some(name), Some(dictionaries)) = (request.json["name"].as_str(), dictionaries) else {
+        return MockResponse::error(400, "Invalid glossary.");
+    };
+    let glossary = MockGlossary {
+        id: random_hex(16),
+        name: name.to_string(),
+        creation_time: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+        dictionaries,
+    };
+    let response = MockResponse::json(glossary.v3_json());
+    state.glossaries.push(glossary);
+    response
+}
+
+fn update_multilingual_glossary(state: &mut MockState, request: &MockRequest, id: &str) -> MockResponse {
+    let dictionaries: Option<Vec<MockDictionary>> = match request.json["dictionaries"].as_array() {
+        Some(dictionaries) => dictionaries.iter().map(MockDictionary::from_json).collect(),
+        None => Some(vec![]),
+    };
+    let Some(dictionaries) = dictionaries else {
+        return MockResponse::error(400, "Invalid dictionary.");
+    };
+    let Some(glossary) = state.glossaries.iter_mut().find(|glossary| glossary.id == id) else {
+        return MockResponse::error(404, "Not found");
+    };
+    if let Some(name) = request.json["name"].as_str() {
+        glossary.name = name.to_string();
+    }
+    for dictionary in dictionaries {
+        glossary.put_dictionary(dictionary, true);
+    }
+    MockResponse::json(glossary.v3_json())
+}
+
+/// Settings of a translation request, after validation and quota accounting.
+struct Translation {
+    source_language: Option<String>,
+    target_language: String,
+    informal: bool,
+    glossary: Vec<(String, String)>,
+    tag_handling: bool,
+    ignore_tags: Vec<String>,
+}
+
+impl Translation {
+    fn new(state: &mut MockState, request: &MockRequest, characters: u64) -> Result<Translation, MockResponse> {
+        let target_language = request.param("target_lang").unwrap_or_default().to_uppercase();
+        // The deprecated codes without variant are still accepted, like by DeepL.
+        let variant = match target_language.as_str() {
+            "EN" => "EN-GB",
+            "PT" => "PT-PT",
+            language => language,
+        };
+        let target = TARGET_LANGUAGES.iter().find(|(language, ..)| *language == variant);
+        let Some((_, _, supports_formality)) = target else {
+            return Err(MockResponse::error(400, "Value for 'target_lang' not supported."));
+        };
+        let source_language = request.param("source_lang").map(str::to_uppercase);
+        if let Some(source_language) = &source_language {
+            if !SOURCE_LANGUAGES.iter().any(|(language, _)| language == source_language) {
+                return Err(MockResponse::error(400, "Value for 'source_lang' not supported."));
+            }
+        }
+        let formality = request.param("formality").unwrap_or("default");
+        if matches!(formality, "more" | "less") && !supports_formality {
+            return Err(MockResponse::error(400, "'formality' is not supported for given 'target_lang'."));
+        }
+        let glossary = match request.param("glossary_id") {
+            Some(id) => {
+                let source = source_language.clone().unwrap_or_default();
+                let target = target_language.split('-').next().unwrap_or_default();
+                match find_glossary(state, id) {
+                    Some(glossary) => glossary.dictionary(&source, target).map(|d| d.entries.clone()).unwrap_or_default(),
+                    None => return Err(MockResponse::error(400, "Glossary not found.")),
+                }
+            }
+            None => vec![],
+        };
+        if state.character_count + characters > state.character_limit {
+            return Err(MockResponse::error(456, "Quota exceeded"));
+        }
+        state.character_count += characters;
+
+        Ok(Translation {
+            source_language,
+            target_language,
+            informal: matches!(formality, "less" | "prefer_less"),
+            glossary,
+            tag_handling: request.param("tag_handling").is_some(),
+            ignore_tags: request.param("ignore_tags").unwrap_or_default().split(',').map(str::to_string).collect(),
+        })
+    }
+
+    /// Translate a text, returning the detected source language and the translation.
+    fn translate(&self, text: &str) -> (String, String) {
+        let source = self.source_language.clone().unwrap_or_else(|| detect_language(text).to_string());
+        let target = self.target_language.split('-').next().unwrap_or_default();
+        if !self.tag_handling {
+            return (source.clone(), self.translate_segment(text, &source, target));
+        }
+
+        // Translate the text between tags, except within ignored tags.
+        let mut translated = String::new();
+        let mut ignored = 0;
+        let mut rest = text;
+        while !rest.is_empty() {
+            let (segment, tag) = match rest.find('<') {
+                Some(0) => {
+                    let end = rest.find('>').map_or(rest.len(), |end| end + 1);
+                    ("", &rest[..end])
+                }
+                Some(start) => (&rest[..start], ""),
+                None => (rest, ""),
+            };
+            match ignored {
+                0 => translated.push_str(&self.translate_segment(segment, &source, target)),
+                _ => translated.push_str(segment),
+            }
+            let tag_name = tag.trim_matches(['<', '>', '/']).split_whitespace().next().unwrap_or_default();
+            if self.ignore_tags.iter().any(|ignore_tag| ignore_tag == tag_name) && !tag.ends_with("/>") {
+                match tag.starts_with("</") {
+                    true => ignored -= 1,
+                    false => ignored += 1,
+                }
+            }
+            translated.push_str(tag);
+            rest = &rest[segment.len() + tag.len()..];
+        }
+        (source, translated)
+    }
+
+    fn translate_segment(&self, segment: &str, source: &str, target: &str) -> String {
+        let content = segment.trim();
+        let translated_content = match (source, target, content) {
+            _ if content.is_empty() || source == target => content.to_string(),
+            _ if self.glossary.iter().any(|(s, _)| s == content) => {
+                self.glossary.iter().find(|(s, _)| s == content).unwrap().1.clone()
+            }
+            ("EN", "DE", "Please go home.") => match self.informal {
+                true => "Bitte geh nach Hause.".to_string(),
+                false => "Bitte gehen Sie nach Hause.".to_string(),
+            },
+            _ => self.translate_words(content, source, target),
+        };
+        let leading = &segment[..segment.len() - segment.trim_start().len()];
+        let trailing = &segment[segment.trim_end().len()..];
+        format!("{}{}{}", leading, translated_content, trailing)
+    }
+
+    /// Translate word by word with the glossary and the built-in dictionary, keeping everything else.
+    fn translate_words(&self, text: &str, source: &str, target: &str) -> String {
+        let dictionary: &[(&str, &str)] = match (source, target) {
+            ("DE", "EN") => &GERMAN_TO_ENGLISH,
+            ("EN", "DE") => &ENGLISH_TO_GERMAN,
+            _ => &[],
+        };
+        let mut translated = String::new();
+        let mut word = String::new();
+        for c in text.chars().chain(std::iter::once('\0')) {
+            if c.is_alphanumeric() {
+                word.push(c);
+                continue;
+            }
+            if !word.is_empty() {
+                let lowercase = word.to_lowercase();
+                match self.glossary.iter().find(|(s, _)| *s == word) {
+                    Some((_, t)) => translated.push_str(t),
+                    None => match dictionary.iter().find(|(s, _)| *s == lowercase) {
+                        Some((_, t)) if word.starts_with(char::is_uppercase) => translated.push_str(&capitalize(t)),
+                        Some((_, t)) => translated.push_str(t),
+                        None => translated.push_str(&word),
+                    },
+                }
+                word.clear();
+            }
+            if c != '\0' {
+                translated.push(c);
+            }
+        }
+        translated
+    }
+}
+
+/// Private helper that guesses whether a text is German or English, based on the built-in dictionary.
+fn detect_language(text: &str) -> &'static str {
+    let words: Vec<String> = text.split(|c: char| !c.is_alphanumeric()).map(str::to_lowercase).collect();
+    let hits = |dictionary: &[(&str, &str)]| words.iter().filter(|word| dictionary.iter().any(|(s, _)| s == word)).count();
+    match hits(&GERMAN_TO_ENGLISH) > hits(&ENGLISH_TO_GERMAN) {
+        true => "DE",
+        false => "EN",
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn random_hex(bytes: usize) -> String {
+    (0..bytes).map(|_| format!("{:02x}", rand::random::<u8>())).collect()
+}
+
+/// Private helper that parses glossary entries in TSV or CSV format.
+fn parse_entries(entries: &str, entries_format: &str) -> Option<Vec<(String, String)>> {
+    let separator = match entries_format {
+        "tsv" => '\t',
+        "csv" => ',',
+        _ => return None,
+    };
+    entries
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| line.split_once(separator).map(|(source, target)| (source.to_string(), target.to_string())))
+        .collect()
+}
+
+fn parse_form(form: &[u8]) -> Vec<(String, String)> {
+    form_urlencoded::parse(form).into_owned().collect()
+}
+
+/// Private helper that extracts the named parts of a `multipart/form-data` body.
+fn parse_multipart(body: &[u8], boundary: &str) -> Vec<(String, Vec<u8>)> {
+    let delimiter = format!("--{}", boundary.trim_matches('"'));
+    let mut parts = vec![];
+    for part in split_bytes(body, delimiter.as_bytes()) {
+        let Some(header_end) = find_bytes(part, b"\r\n\r\n") else {
+            continue;
+        };
+        let headers = String::from_utf8_lossy(&part[..header_end]);
+        let Some(name) = headers.split("name=\"").nth(1).and_then(|rest| rest.split('"').next()) else {
+            continue;
+        };
+        let content = &part[header_end + 4..];
+        let content = content.strip_suffix(b"\r\n").unwrap_or(content);
+        parts.push((name.to_string(), content.to_vec()));
+    }
+    parts
+}
+
+fn find_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+fn split_bytes<'a>(mut bytes: &'a [u8], delimiter: &[u8]) -> Vec<&'a [u8]> {
+    let mut parts = vec![];
+    while let Some(position) = find_bytes(bytes, delimiter) {
+        parts.push(&bytes[..position]);
+        bytes = &bytes[position + delimiter.len()..];
+    }
+    parts.push(bytes);
+    parts
+}
+
+/// Private helper for the tests of this crate, which translate German texts into American English.
+#[cfg(test)]
+pub(crate) fn text_list(texts: &[&str]) -> crate::TranslatableTextList {
+    crate::TranslatableTextList {
+        source_language: Some("DE".into()),
+        target_language: "EN-US".into(),
+        texts: texts.iter().map(|text| text.to_string()).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ErrorKind, RetryPolicy, TranslatableTextList};
+
+    #[test]
+    fn translate() {
+        let server = MockServer::start();
+        let translated = server.deepl().translate(None, TranslatableTextList {
+            source_language: None,
+            ..text_list(&["Hallo Welt!", "Hello world!"])
+        }).unwrap();
+        assert_eq!(translated[0].text, "Hello World!");
+        assert_eq!(translated[0].detected_source_language, "DE");
+        assert_eq!(translated[1].text, "Hello world!");
+        assert_eq!(translated[1].detected_source_language, "EN");
+        assert_eq!(server.character_count(), 23);
+    }
+
+    #[test]
+    fn quota() {
+        let server = MockServer::start();
+        server.set_character_limit(10);
+        let deepl = server.deepl();
+        deepl.translate(None, text_list(&["ja"])).unwrap();
+        let error = deepl.translate(None, text_list(&["Hallo Welt!"])).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::QuotaExceeded(456, _)));
+        assert_eq!(deepl.usage_information().unwrap().remaining_characters(), 8);
+    }
+
+    #[test]
+    fn inject_error() {
+        let server = MockServer::start();
+        server.inject_error(429, "Too many requests");
+        server.inject_error(529, "Too many requests");
+        let deepl = server.builder().retry_policy(RetryPolicy::default()).build().unwrap();
+        assert_eq!(deepl.translate(None, text_list(&["ja"])).unwrap()[0].text, "yes");
+        assert_eq!(server.request_count(), 3);
+
+        server.inject_error(500, "Internal error");
+        let error = deepl.usage_information().unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::ServerError(message) if message == "Internal error: "));
+    }
+
+    #[test]
+    fn formality() {
+        let server = MockServer::start();
+        let translate = |target_language: &str, formality: &str| {
+            reqwest::blocking::Client::new()
+                .post(format!("{}/v2/translate", server.url()))
+                .header("Authorization", format!("DeepL-Auth-Key {}", MockServer::API_KEY))
+                .form(&[("text", "ja"), ("target_lang", target_language), ("formality", formality)])
+                .send()
+                .unwrap()
+                .status()
+                .as_u16()
+        };
+        assert_eq!(translate("DE", "more"), 200);
+        assert_eq!(translate("PT", "less"), 200);
+        assert_eq!(translate("PT-BR", "less"), 200);
+        assert_eq!(translate("EN", "more"), 400);
+        assert_eq!(translate("EN-US", "more"), 400);
+        assert_eq!(translate("EN", "default"), 200);
+        assert_eq!(translate("XX", "default"), 400);
+    }
+
+    #[test]
+    fn unauthorized() {
+        let server = MockServer::start();
+        let deepl = DeepL::builder("wrong_key".to_string()).server_url(server.url()).build().unwrap();
+        assert!(matches!(deepl.usage_information().unwrap_err().kind(), ErrorKind::AuthorizationError));
+    }
+
+    #[test]
+    fn parse_multipart() {
+        let body = b"--XYZ\r\nContent-Disposition: form-data; name=\"target_lang\"\r\n\r\nDE\r\n--XYZ\r\nContent-Disposition: form-data; name=\"file\"; filename=\"a.txt\"\r\nContent-Type: text/plain\r\n\r\nline\r\n\r\n--XYZ--\r\n";
+        assert_eq!(super::parse_multipart(body, "XYZ"), vec![
+            ("target_lang".to_string(), b"DE".to_vec()),
+            ("file".to_string(), b"line\r\n".to_vec()),
+        ]);
+    }
+}
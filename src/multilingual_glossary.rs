@@ -0,0 +1,278 @@
+use crate::{parse_glossary_entries, DeepL, ErrorKind, GlossaryEntries, GlossaryEntriesFormat, Language, Payload, Result};
+use chrono::{DateTime, Utc};
+use error_chain::*;
+use reqwest::{blocking::Response, Method};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// Entries of a multilingual glossary for one language pair.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct GlossaryDictionary {
+    /// The language in which the source texts are specified.
+    pub source_lang: Language,
+    /// The language in which the target texts are specified.
+    pub target_lang: Language,
+    /// The entries, formatted according to `entries_format`.
+    pub entries: String,
+    /// The format of the entries.
+    pub entries_format: GlossaryEntriesFormat,
+}
+
+/// Information about a dictionary of a multilingual glossary.
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct GlossaryDictionaryInfo {
+    /// The language in which the source texts are specified.
+    pub source_lang: Language,
+    /// The language in which the target texts are specified.
+    pub target_lang: Language,
+    /// The number of entries in the dictionary.
+    pub entry_count: u64,
+}
+
+/// Representation of a multilingual glossary, which can hold dictionaries for several language pairs.
+#[derive(Debug, Deserialize)]
+pub struct MultilingualGlossary {
+    /// A unique ID assigned to a glossary. It stays the same when dictionaries are changed.
+    pub glossary_id: String,
+    /// Name associated with the glossary.
+    pub name: String,
+    /// The dictionaries of the glossary, one per language pair.
+    pub dictionaries: Vec<GlossaryDictionaryInfo>,
+    /// The creation time of the glossary.
+    pub creation_time: DateTime<Utc>,
+}
+
+/// Representation of a multilingual glossary listing response.
+#[derive(Debug, Deserialize)]
+pub struct MultilingualGlossaryListing {
+    /// A list of glossaries.
+    pub glossaries: Vec<MultilingualGlossary>,
+}
+
+// Only needed for JSON deserialization.
+#[derive(Debug, Deserialize)]
+pub(crate) struct GlossaryDictionaryList {
+    dictionaries: Vec<GlossaryDictionary>,
+}
+
+impl GlossaryDictionaryList {
+    /// Private method that parses the entries of the single dictionary in an entries response.
+    pub(crate) fn into_entries(self) -> Result<GlossaryEntries> {
+        match self.dictionaries.into_iter().next() {
+            Some(dictionary) => parse_glossary_entries(&dictionary.entries),
+            None => bail!(ErrorKind::DeserializationError),
+        }
+    }
+}
+
+impl GlossaryDictionary {
+    /// Private method that drops the regional variants of the languages, as dictionaries are defined
+    /// for languages without them.
+    pub(crate) fn without_variants(self) -> GlossaryDictionary {
+        GlossaryDictionary {
+            source_lang: self.source_lang.to_source(),
+            target_lang: self.target_lang.to_source(),
+            ..self
+        }
+    }
+}
+
+/// Private helper that builds the request body for creating or updating a multilingual glossary.
+pub(crate) fn multilingual_glossary_body(name: Option<String>, dictionaries: Vec<GlossaryDictionary>) -> serde_json::Value {
+    let mut body = json!({});
+    if let Some(name) = name {
+        body["name"] = json!(name);
+    }
+    if !dictionaries.is_empty() {
+        let dictionaries: Vec<GlossaryDictionary> = dictionaries.into_iter().map(GlossaryDictionary::without_variants).collect();
+        body["dictionaries"] = json!(dictionaries);
+    }
+    body
+}
+
+/// Private helper that builds the request parameters selecting one dictionary of a multilingual glossary.
+pub(crate) fn dictionary_params(source_lang: Language, target_lang: Language) -> [(&'static str, String); 2] {
+    [
+        ("source_lang", source_lang.to_source().into()),
+        ("target_lang", target_lang.to_source().into()),
+    ]
+}
+
+/// Multilingual glossaries (v3 API). See also the [vendor documentation](https://developers.deepl.com/docs/api-reference/multilingual-glossaries).
+impl DeepL {
+    /// Create a multilingual glossary with one dictionary per language pair.
+    pub fn create_multilingual_glossary(
+        &self,
+        name: String,
+        dictionaries: Vec<GlossaryDictionary>,
+    ) -> Result<MultilingualGlossary> {
+        let body = multilingual_glossary_body(Some(name), dictionaries);
+        let res = self.http_request(Method::POST, "/v3/glossaries", Payload::Json(&body))?;
+
+        match res.json::<MultilingualGlossary>() {
+            Ok(content) => Ok(content),
+            _ => bail!(ErrorKind::DeserializationError),
+        }
+    }
+
+    /// List all multilingual glossaries.
+    pub fn list_multilingual_glossaries(&self) -> Result<MultilingualGlossaryListing> {
+        let res = self.http_request(Method::GET, "/v3/glossaries", Payload::None)?;
+
+        match res.json::<MultilingualGlossaryListing>() {
+            Ok(content) => Ok(content),
+            _ => bail!(ErrorKind::DeserializationError),
+        }
+    }
+
+    /// Retrieve details of a multilingual glossary.
+    pub fn get_multilingual_glossary(&self, glossary_id: String) -> Result<MultilingualGlossary> {
+        let res = self.http_request(Method::GET, &format!("/v3/glossaries/{}", glossary_id), Payload::None)?;
+
+        match res.json::<MultilingualGlossary>() {
+            Ok(content) => Ok(content),
+            _ => bail!(ErrorKind::DeserializationError),
+        }
+    }
+
+    /// Delete a multilingual glossary with all its dictionaries.
+    pub fn delete_multilingual_glossary(&self, glossary_id: String) -> Result<Response> {
+        self.http_request(Method::DELETE, &format!("/v3/glossaries/{}", glossary_id), Payload::None)
+    }
+
+    /// Retrieve the entries of the dictionary for one language pair of a multilingual glossary.
+    pub fn get_multilingual_glossary_entries(
+        &self,
+        glossary_id: String,
+        source_lang: Language,
+        target_lang: Language,
+    ) -> Result<GlossaryEntries> {
+        let res = self.http_request(
+            Method::GET,
+            &format!("/v3/glossaries/{}/entries", glossary_id),
+            Payload::Params(&dictionary_params(source_lang, target_lang)),
+        )?;
+
+        match res.json::<GlossaryDictionaryList>() {
+            Ok(content) => content.into_entries(),
+            _ => bail!(ErrorKind::DeserializationError),
+        }
+    }
+
+    /// Rename a multilingual glossary.
+    pub fn rename_multilingual_glossary(&self, glossary_id: String, name: String) -> Result<MultilingualGlossary> {
+        let body = multilingual_glossary_body(Some(name), vec![]);
+        let res = self.http_request(Method::PATCH, &format!("/v3/glossaries/{}", glossary_id), Payload::Json(&body))?;
+
+        match res.json::<MultilingualGlossary>() {
+            Ok(content) => Ok(content),
+            _ => bail!(ErrorKind::DeserializationError),
+        }
+    }
+
+    /// Merge entries into the dictionary of a multilingual glossary. Existing entries with the same source
+    /// text are overwritten, other existing entries are kept. The dictionary is added if the glossary
+    /// does not contain one for this language pair yet.
+    pub fn update_glossary_dictionary(
+        &self,
+        glossary_id: String,
+        dictionary: GlossaryDictionary,
+    ) -> Result<MultilingualGlossary> {
+        let body = multilingual_glossary_body(None, vec![dictionary]);
+        let res = self.http_request(Method::PATCH, &format!("/v3/glossaries/{}", glossary_id), Payload::Json(&body))?;
+
+        match res.json::<MultilingualGlossary>() {
+            Ok(content) => Ok(content),
+            _ => bail!(ErrorKind::DeserializationError),
+        }
+    }
+
+    /// Replace all entries of the dictionary of a multilingual glossary. The dictionary is added if the
+    /// glossary does not contain one for this language pair yet.
+    pub fn replace_glossary_dictionary(
+        &self,
+        glossary_id: String,
+        dictionary: GlossaryDictionary,
+    ) -> Result<GlossaryDictionaryInfo> {
+        let body = json!(dictionary.without_variants());
+        let res = self.http_request(Method::PUT, &format!("/v3/glossaries/{}/dictionaries", glossary_id), Payload::Json(&body))?;
+
+        match res.json::<GlossaryDictionaryInfo>() {
+            Ok(content) => Ok(content),
+            _ => bail!(ErrorKind::DeserializationError),
+        }
+    }
+
+    /// Remove the dictionary for one language pair from a multilingual glossary.
+    pub fn delete_glossary_dictionary(
+        &self,
+        glossary_id: String,
+        source_lang: Language,
+        target_lang: Language,
+    ) -> Result<Response> {
+        self.http_request(
+            Method::DELETE,
+            &format!("/v3/glossaries/{}/dictionaries", glossary_id),
+            Payload::Params(&dictionary_params(source_lang, target_lang)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dictionary(source_lang: &str, target_lang: &str, entries: &str) -> GlossaryDictionary {
+        GlossaryDictionary {
+            source_lang: source_lang.into(),
+            target_lang: target_lang.into(),
+            entries: entries.to_string(),
+            entries_format: GlossaryEntriesFormat::Tsv,
+        }
+    }
+
+    #[test]
+    fn multilingual_glossary_body() {
+        let body = super::multilingual_glossary_body(Some("test".to_string()), vec![dictionary("en-us", "de", "Action\tHandlung")]);
+        assert_eq!(body.to_string(), r#"{"dictionaries":[{"entries":"Action\tHandlung","entries_format":"tsv","source_lang":"EN","target_lang":"DE"}],"name":"test"}"#);
+
+        let body = super::multilingual_glossary_body(Some("test".to_string()), vec![]);
+        assert_eq!(body.to_string(), r#"{"name":"test"}"#);
+    }
+
+    #[test]
+    fn multilingual_glossaries() {
+        let server = crate::mock::MockServer::start();
+        let deepl = server.deepl();
+        let glossary_name = "test_multilingual_glossary".to_string();
+
+        let glossary = deepl.create_multilingual_glossary(
+            glossary_name.clone(),
+            vec![dictionary("en", "de", "Action\tHandlung"), dictionary("de", "en", "Handlung\tAction")],
+        ).unwrap();
+        assert_eq!(glossary.name, glossary_name);
+        assert_eq!(glossary.dictionaries.len(), 2);
+
+        let updated = deepl.update_glossary_dictionary(glossary.glossary_id.clone(), dictionary("en", "de", "Home\tZuhause")).unwrap();
+        assert_eq!(updated.glossary_id, glossary.glossary_id);
+        let entries = deepl.get_multilingual_glossary_entries(glossary.glossary_id.clone(), "en".into(), "de".into()).unwrap();
+        assert_eq!(entries.len(), 2);
+
+        let replaced = deepl.replace_glossary_dictionary(glossary.glossary_id.clone(), dictionary("en", "de", "Go\tGehen")).unwrap();
+        assert_eq!(replaced.entry_count, 1);
+        let entries = deepl.get_multilingual_glossary_entries(glossary.glossary_id.clone(), "en".into(), "de".into()).unwrap();
+        assert_eq!(entries, vec![("Go".to_string(), "Gehen".to_string())]);
+
+        deepl.delete_glossary_dictionary(glossary.glossary_id.clone(), "de".into(), "en".into()).unwrap();
+        let glossary = deepl.get_multilingual_glossary(glossary.glossary_id).unwrap();
+        assert_eq!(glossary.dictionaries, vec![GlossaryDictionaryInfo {
+            source_lang: "en".into(),
+            target_lang: "de".into(),
+            entry_count: 1,
+        }]);
+
+        deepl.delete_multilingual_glossary(glossary.glossary_id.clone()).unwrap();
+        let glossary_response = deepl.get_multilingual_glossary(glossary.glossary_id);
+        assert_eq!(glossary_response.unwrap_err().to_string(), crate::ErrorKind::NotFoundError.to_string());
+    }
+}
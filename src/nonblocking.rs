@@ -0,0 +1,743 @@
+//! Asynchronous variant of the API client, available with the `async` cargo feature.
+//!
+//! [nonblocking::DeepL](DeepL) offers the same functions as the blocking [crate::DeepL], but as
+//! `async fn`s that can be used from within an async runtime like `tokio`. All option and result types
+//! are shared between both clients, so switching between them only requires adding `.await`.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use deepl_api::*;
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let deepl = nonblocking::DeepL::new(std::env::var("DEEPL_API_KEY").unwrap());
+//!
+//! let texts = TranslatableTextList {
+//!     source_language: Some("DE".into()),
+//!     target_language: "EN-US".into(),
+//!     texts: vec!("ja".to_string()),
+//! };
+//! let translated = deepl.translate(None, texts).await.unwrap();
+//! assert_eq!(translated[0].text, "yes");
+//! # }
+//! ```
+
+use crate::{
+    batch::{split_text_list, BatchTranslations}, billable_characters, billable_file_characters, builder::Settings, chunking::{ChunkedTextList, MAX_CHUNK_SIZE}, check_glossary_language_pair, default_server_url, dictionary_params, document_params,
+    error_for_status, glossary_params, multilingual_glossary_body, parse_glossary_entries,
+    translation_params, Cassette, CostEstimate, DeepLBuilder, DocumentHandle, DocumentStatus, DocumentTranslationOptions,
+    Error, ErrorKind, Glossary, GlossaryDictionary, GlossaryDictionaryInfo, GlossaryDictionaryList,
+    GlossaryEntries, GlossaryEntriesFormat, GlossaryLanguagePair, GlossaryLanguagePairList,
+    GlossaryListing, Language, LanguageList, MultilingualGlossary, MultilingualGlossaryListing, Payload, RecordedRequest,
+    Result, ServerErrorMessage, TranslatableDocument, TranslatableTextList, TranslatedDocument,
+    TranslatedText, TranslatedTextList, TranslationOptions, UsageInformation,
+};
+use error_chain::*;
+use futures_util::{stream, StreamExt};
+use reqwest::{self, Method, Response};
+use serde_json::json;
+use std::future::Future;
+use std::path::Path;
+
+/// The asynchronous API entry point representing a DeepL developer account with an associated API key.
+///
+/// See [crate::DeepL] for details, this struct behaves exactly the same way.
+pub struct DeepL {
+    pub(crate) api_key: String,
+    pub(crate) server_url: String,
+    pub(crate) client: reqwest::Client,
+    pub(crate) settings: Settings,
+}
+
+/// Implements the actual REST API. See also the [online documentation](https://www.deepl.com/docs-api/).
+impl DeepL {
+    /// Use this to create a new asynchronous DeepL API client instance where multiple function calls can be performed.
+    /// A valid `api_key` is required.
+    pub fn new(api_key: String) -> DeepL {
+        DeepL {
+            server_url: default_server_url(&api_key).to_string(),
+            api_key,
+            client: reqwest::Client::new(),
+            settings: Settings::default(),
+        }
+    }
+
+    /// Use this to create an asynchronous client with custom settings, finishing with
+    /// [DeepLBuilder::build_async]. See [DeepLBuilder] for the available options.
+    pub fn builder(api_key: String) -> DeepLBuilder {
+        DeepLBuilder::new(api_key)
+    }
+
+    /// Private method that performs the HTTP calls.
+    async fn http_request(
+        &self,
+        method: Method,
+        url: &str,
+        payload: Payload<'_>,
+    ) -> Result<Response> {
+
+        let path = url;
+        let url = format!("{}{}", self.server_url, path);
+
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let response = match &self.settings.cassette {
+                Some(cassette) => self.cassette_request(cassette, &method, path, &url, payload).await,
+                None => self.send_request(&method, &url, payload).await.map_err(Error::from),
+            };
+
+            match response {
+                Ok(response) if response.status().is_success() => return Ok(response),
+                Ok(response) if self.settings.retry_policy.should_retry(response.status(), attempt) => {
+                    let delay = self.settings.retry_policy.backoff(attempt, response.headers());
+                    tokio::time::sleep(delay).await;
+                }
+                // DeepL sends back error messages in the response body.
+                //   Try to fetch them to construct more helpful exceptions.
+                Ok(response) => {
+                    let status = response.status();
+                    let headers = response.headers().clone();
+                    let error = error_for_status(status, &headers, response.json::<ServerErrorMessage>().await.ok());
+                    return Err(self.settings.retry_policy.final_error(error.into(), attempt));
+                }
+                Err(e) => return Err(self.settings.retry_policy.final_error(e, attempt)),
+            }
+        }
+    }
+
+    /// Private method that sends a single request.
+    async fn send_request(
+        &self,
+        method: &Method,
+        url: &str,
+        payload: Payload<'_>,
+    ) -> reqwest::Result<Response> {
+        let request = self.client.request(method.clone(), url).header("Authorization", format!("DeepL-Auth-Key {}", self.api_key));
+
+        match payload {
+            Payload::None => request.send().await,
+            Payload::Params(params) => match *method {
+                Method::GET | Method::DELETE => request.query(params).send().await,
+                _ => request.form(params).send().await,
+            },
+            Payload::Json(body) => request.json(body).send().await,
+            Payload::File(params, filename, content) => {
+                let mut form = reqwest::multipart::Form::new();
+                for (name, value) in params {
+                    form = form.text(name.to_string(), value.clone());
+                }
+                let file = reqwest::multipart::Part::bytes(content.to_vec()).file_name(filename.to_string());
+                request.multipart(form.part("file", file)).send().await
+            }
+        }
+    }
+
+    /// Private method that answers a request from the [Cassette], or sends it and records the response.
+    async fn cassette_request(
+        &self,
+        cassette: &Cassette,
+        method: &Method,
+        path: &str,
+        url: &str,
+        payload: Payload<'_>,
+    ) -> Result<Response> {
+        let request = RecordedRequest::new(method, path, payload, &self.api_key);
+        if !cassette.is_recording() {
+            return Ok(cassette.replay_response(&request)?.into());
+        }
+        let response = self.send_request(method, url, payload).await?;
+        let (status, headers) = (response.status(), response.headers().clone());
+        let body = response.bytes().await?.to_vec();
+        let body = {
+            let (cassette, headers) = (cassette.clone(), headers.clone());
+            blocking(move || cassette.record_response(request, status, &headers, &body).map(|_| body)).await?
+        };
+
+        let mut response = http::Response::new(body);
+        *response.status_mut() = status;
+        *response.headers_mut() = headers;
+        Ok(response.into())
+    }
+
+    /// Retrieve information about API usage & limits.
+    ///
+    /// See [crate::DeepL::usage_information].
+    pub async fn usage_information(&self) -> Result<UsageInformation> {
+        let res = self.http_request(Method::POST, "/v2/usage", Payload::None).await?;
+
+        match res.json::<UsageInformation>().await {
+            Ok(content) => Ok(content),
+            _ => bail!(ErrorKind::DeserializationError),
+        }
+    }
+
+    /// Estimate the billed characters for translating the texts.
+    ///
+    /// See [crate::DeepL::estimate].
+    pub async fn estimate(&self, text_list: &TranslatableTextList) -> Result<CostEstimate> {
+        Ok(CostEstimate::new(billable_characters(text_list), &self.usage_information().await?))
+    }
+
+    /// Estimate the billed characters for translating text files.
+    ///
+    /// See [crate::DeepL::estimate_files].
+    pub async fn estimate_files(&self, files: &[impl AsRef<Path>], target_languages: &[Language]) -> Result<CostEstimate> {
+        let characters = billable_file_characters(files, target_languages)?;
+        Ok(CostEstimate::new(characters, &self.usage_information().await?))
+    }
+
+    /// Retrieve all currently available source languages.
+    ///
+    /// See [crate::DeepL::source_languages].
+    pub async fn source_languages(&self) -> Result<LanguageList> {
+        self.languages("source").await
+    }
+
+    /// Retrieve all currently available target languages.
+    ///
+    /// See [crate::DeepL::target_languages].
+    pub async fn target_languages(&self) -> Result<LanguageList> {
+        self.languages("target").await
+    }
+
+    /// Private method to make the API calls for the language lists.
+    async fn languages(&self, language_type: &str) -> Result<LanguageList> {
+        let res = self.http_request(Method::POST, "/v2/languages", Payload::Params(&[("type", language_type.to_string())])).await?;
+
+        match res.json::<LanguageList>().await {
+            Ok(content) => Ok(content),
+            _ => bail!(ErrorKind::DeserializationError),
+        }
+    }
+
+    /// Translate one or more [text chunks](TranslatableTextList) at once.
+    ///
+    /// See [crate::DeepL::translate].
+    pub async fn translate(
+        &self,
+        options: Option<TranslationOptions>,
+        text_list: TranslatableTextList,
+    ) -> Result<Vec<TranslatedText>> {
+        let Some(cache) = self.settings.cache.clone() else {
+            return self.translate_uncached(options, text_list).await?.into_result();
+        };
+        let (lookup, misses) = {
+            let (cache, options) = (cache.clone(), options.clone());
+            blocking(move || cache.lookup(&options, text_list)).await?
+        };
+        let translations = match misses.texts.is_empty() {
+            true => BatchTranslations::default(),
+            false => self.translate_uncached(options, misses).await?,
+        };
+        blocking(move || lookup.complete(&cache, translations)).await
+    }
+
+    /// Private method that translates a text list in batches, bypassing the cache.
+    async fn translate_uncached(
+        &self,
+        options: Option<TranslationOptions>,
+        text_list: TranslatableTextList,
+    ) -> Result<BatchTranslations> {
+        let Some(budget) = &self.settings.budget else {
+            return self.translate_batches(options, text_list).await;
+        };
+        while !budget.reserve(&text_list)? {
+            budget.set_usage(&self.usage_information().await?);
+        }
+        let translations = self.translate_batches(options, text_list).await?;
+        if translations.error.is_some() {
+            // Some of the batches may have been billed, so the usage has to be fetched again.
+            budget.reset();
+        }
+        Ok(translations)
+    }
+
+    /// Private method that translates a text list in batches. No further batches are sent once a
+    /// batch failed.
+    async fn translate_batches(
+        &self,
+        options: Option<TranslationOptions>,
+        text_list: TranslatableTextList,
+    ) -> Result<BatchTranslations> {
+        let requests = split_text_list(&options, text_list)?.into_iter().map(|batch| {
+            let size = batch.texts.len();
+            let request = self.translate_batch(options.clone(), batch);
+            async move { (size, request.await) }
+        });
+        let mut results = stream::iter(requests).buffered(self.settings.parallel_requests);
+        let mut translations = BatchTranslations::default();
+        while let Some((size, result)) = results.next().await {
+            translations.push(size, result);
+            if translations.error.is_some() {
+                break;
+            }
+        }
+        Ok(translations)
+    }
+
+    /// Private method that translates a text list which fits into a single request.
+    async fn translate_batch(
+        &self,
+        options: Option<TranslationOptions>,
+        text_list: TranslatableTextList,
+    ) -> Result<Vec<TranslatedText>> {
+        let query = translation_params(options, text_list)?;
+
+        let res = self.http_request(Method::POST, "/v2/translate", Payload::Params(&query)).await?;
+
+        match res.json::<TranslatedTextList>().await {
+            Ok(content) => Ok(content.translations),
+            _ => bail!(ErrorKind::DeserializationError),
+        }
+    }
+
+    /// Translate texts of any length, splitting them into chunks if necessary.
+    ///
+    /// See [crate::DeepL::translate_long_texts].
+    pub async fn translate_long_texts(
+        &self,
+        options: Option<TranslationOptions>,
+        text_list: TranslatableTextList,
+    ) -> Result<Vec<TranslatedText>> {
+        let chunked = ChunkedTextList::new(&options, text_list, MAX_CHUNK_SIZE);
+        let chunk_list = chunked.text_list();
+        let translations = match chunk_list.texts.is_empty() {
+            true => vec![],
+            false => self.translate(options, chunk_list).await?,
+        };
+        chunked.reassemble(translations)
+    }
+
+    /// Create a glossary.
+    ///
+    /// See [crate::DeepL::create_glossary].
+    pub async fn create_glossary(
+        &self,
+        name: String,
+        source_lang: Language,
+        target_lang: Language,
+        entries: String,
+        entries_format: GlossaryEntriesFormat
+    ) -> Result<Glossary> {
+        if self.settings.validate_glossary_languages {
+            check_glossary_language_pair(&self.glossary_language_pairs().await?, &source_lang, &target_lang)?;
+        }
+        let params = glossary_params(name, source_lang, target_lang, entries, entries_format);
+        let res = self.http_request(Method::POST, "/v2/glossaries", Payload::Params(&params)).await?;
+
+        match res.json::<Glossary>().await {
+            Ok(content) => Ok(content),
+            _ => bail!(ErrorKind::DeserializationError),
+        }
+    }
+
+    /// Retrieve the language pairs that are supported for glossaries.
+    ///
+    /// See [crate::DeepL::glossary_language_pairs].
+    pub async fn glossary_language_pairs(&self) -> Result<Vec<GlossaryLanguagePair>> {
+        let res = self.http_request(Method::GET, "/v2/glossary-language-pairs", Payload::None).await?;
+
+        match res.json::<GlossaryLanguagePairList>().await {
+            Ok(content) => Ok(content.supported_languages),
+            _ => bail!(ErrorKind::DeserializationError),
+        }
+    }
+
+    /// List all glossaries.
+    ///
+    /// See [crate::DeepL::list_glossaries].
+    pub async fn list_glossaries(&self) -> Result<GlossaryListing> {
+        let res = self.http_request(Method::GET, "/v2/glossaries", Payload::None).await?;
+
+        match res.json::<GlossaryListing>().await {
+            Ok(content) => Ok(content),
+            _ => bail!(ErrorKind::DeserializationError),
+        }
+    }
+
+    /// Delete a glossary.
+    ///
+    /// See [crate::DeepL::delete_glossary].
+    pub async fn delete_glossary(&self, glossary_id: String) -> Result<Response> {
+        self.http_request(Method::DELETE, &format!("/v2/glossaries/{}", glossary_id), Payload::None).await
+    }
+
+    /// Retrieve Glossary Details.
+    ///
+    /// See [crate::DeepL::get_glossary].
+    pub async fn get_glossary(&self, glossary_id: String) -> Result<Glossary> {
+        let res = self.http_request(Method::GET, &format!("/v2/glossaries/{}", glossary_id), Payload::None).await?;
+
+        match res.json::<Glossary>().await {
+            Ok(content) => Ok(content),
+            _ => bail!(ErrorKind::DeserializationError),
+        }
+    }
+
+    /// Retrieve the entries of a glossary.
+    ///
+    /// See [crate::DeepL::get_glossary_entries].
+    pub async fn get_glossary_entries(&self, glossary_id: String) -> Result<GlossaryEntries> {
+        let res = self.http_request(Method::GET, &format!("/v2/glossaries/{}/entries", glossary_id), Payload::None).await?;
+
+        match res.text().await {
+            Ok(content) => parse_glossary_entries(&content),
+            _ => bail!(ErrorKind::DeserializationError),
+        }
+    }
+
+    /// Create a multilingual glossary.
+    ///
+    /// See [crate::DeepL::create_multilingual_glossary].
+    pub async fn create_multilingual_glossary(
+        &self,
+        name: String,
+        dictionaries: Vec<GlossaryDictionary>,
+    ) -> Result<MultilingualGlossary> {
+        let body = multilingual_glossary_body(Some(name), dictionaries);
+        let res = self.http_request(Method::POST, "/v3/glossaries", Payload::Json(&body)).await?;
+
+        match res.json::<MultilingualGlossary>().await {
+            Ok(content) => Ok(content),
+            _ => bail!(ErrorKind::DeserializationError),
+        }
+    }
+
+    /// List all multilingual glossaries.
+    ///
+    /// See [crate::DeepL::list_multilingual_glossaries].
+    pub async fn list_multilingual_glossaries(&self) -> Result<MultilingualGlossaryListing> {
+        let res = self.http_request(Method::GET, "/v3/glossaries", Payload::None).await?;
+
+        match res.json::<MultilingualGlossaryListing>().await {
+            Ok(content) => Ok(content),
+            _ => bail!(ErrorKind::DeserializationError),
+        }
+    }
+
+    /// Retrieve details of a multilingual glossary.
+    ///
+    /// See [crate::DeepL::get_multilingual_glossary].
+    pub async fn get_multilingual_glossary(&self, glossary_id: String) -> Result<MultilingualGlossary> {
+        let res = self.http_request(Method::GET, &format!("/v3/glossaries/{}", glossary_id), Payload::None).await?;
+
+        match res.json::<MultilingualGlossary>().await {
+            Ok(content) => Ok(content),
+            _ => bail!(ErrorKind::DeserializationError),
+        }
+    }
+
+    /// Delete a multilingual glossary.
+    ///
+    /// See [crate::DeepL::delete_multilingual_glossary].
+    pub async fn delete_multilingual_glossary(&self, glossary_id: String) -> Result<Response> {
+        self.http_request(Method::DELETE, &format!("/v3/glossaries/{}", glossary_id), Payload::None).await
+    }
+
+    /// Retrieve the entries of one dictionary of a multilingual glossary.
+    ///
+    /// See [crate::DeepL::get_multilingual_glossary_entries].
+    pub async fn get_multilingual_glossary_entries(
+        &self,
+        glossary_id: String,
+        source_lang: Language,
+        target_lang: Language,
+    ) -> Result<GlossaryEntries> {
+        let res = self.http_request(
+            Method::GET,
+            &format!("/v3/glossaries/{}/entries", glossary_id),
+            Payload::Params(&dictionary_params(source_lang, target_lang)),
+        ).await?;
+
+        match res.json::<GlossaryDictionaryList>().await {
+            Ok(content) => content.into_entries(),
+            _ => bail!(ErrorKind::DeserializationError),
+        }
+    }
+
+    /// Rename a multilingual glossary.
+    ///
+    /// See [crate::DeepL::rename_multilingual_glossary].
+    pub async fn rename_multilingual_glossary(&self, glossary_id: String, name: String) -> Result<MultilingualGlossary> {
+        let body = multilingual_glossary_body(Some(name), vec![]);
+        let res = self.http_request(Method::PATCH, &format!("/v3/glossaries/{}", glossary_id), Payload::Json(&body)).await?;
+
+        match res.json::<MultilingualGlossary>().await {
+            Ok(content) => Ok(content),
+            _ => bail!(ErrorKind::DeserializationError),
+        }
+    }
+
+    /// Merge entries into a dictionary of a multilingual glossary.
+    ///
+    /// See [crate::DeepL::update_glossary_dictionary].
+    pub async fn update_glossary_dictionary(
+        &self,
+        glossary_id: String,
+        dictionary: GlossaryDictionary,
+    ) -> Result<MultilingualGlossary> {
+        let body = multilingual_glossary_body(None, vec![dictionary]);
+        let res = self.http_request(Method::PATCH, &format!("/v3/glossaries/{}", glossary_id), Payload::Json(&body)).await?;
+
+        match res.json::<MultilingualGlossary>().await {
+            Ok(content) => Ok(content),
+            _ => bail!(ErrorKind::DeserializationError),
+        }
+    }
+
+    /// Replace all entries of a dictionary of a multilingual glossary.
+    ///
+    /// See [crate::DeepL::replace_glossary_dictionary].
+    pub async fn replace_glossary_dictionary(
+        &self,
+        glossary_id: String,
+        dictionary: GlossaryDictionary,
+    ) -> Result<GlossaryDictionaryInfo> {
+        let body = json!(dictionary.without_variants());
+        let res = self.http_request(Method::PUT, &format!("/v3/glossaries/{}/dictionaries", glossary_id), Payload::Json(&body)).await?;
+
+        match res.json::<GlossaryDictionaryInfo>().await {
+            Ok(content) => Ok(content),
+            _ => bail!(ErrorKind::DeserializationError),
+        }
+    }
+
+    /// Remove the dictionary for one language pair from a multilingual glossary.
+    ///
+    /// See [crate::DeepL::delete_glossary_dictionary].
+    pub async fn delete_glossary_dictionary(
+        &self,
+        glossary_id: String,
+        source_lang: Language,
+        target_lang: Language,
+    ) -> Result<Response> {
+        self.http_request(
+            Method::DELETE,
+            &format!("/v3/glossaries/{}/dictionaries", glossary_id),
+            Payload::Params(&dictionary_params(source_lang, target_lang)),
+        ).await
+    }
+
+    /// Upload a [document](TranslatableDocument) for translation.
+    ///
+    /// See [crate::DeepL::upload_document].
+    pub async fn upload_document(
+        &self,
+        options: Option<DocumentTranslationOptions>,
+        document: TranslatableDocument,
+    ) -> Result<DocumentHandle> {
+        let params = document_params(options, &document)?;
+        let res = self.http_request(
+            Method::POST,
+            "/v2/document",
+            Payload::File(&params, &document.filename, &document.content),
+        ).await?;
+
+        match res.json::<DocumentHandle>().await {
+            Ok(content) => Ok(content),
+            _ => bail!(ErrorKind::DeserializationError),
+        }
+    }
+
+    /// Retrieve the current status of a document translation.
+    ///
+    /// See [crate::DeepL::document_status].
+    pub async fn document_status(&self, handle: &DocumentHandle) -> Result<DocumentStatus> {
+        let res = self.http_request(
+            Method::POST,
+            &format!("/v2/document/{}", handle.document_id),
+            Payload::Params(&[("document_key", handle.document_key.clone())]),
+        ).await?;
+
+        match res.json::<DocumentStatus>().await {
+            Ok(content) => Ok(content),
+            _ => bail!(ErrorKind::DeserializationError),
+        }
+    }
+
+    /// Wait until the translation of a document is done.
+    ///
+    /// See [crate::DeepL::wait_for_document].
+    pub async fn wait_for_document(&self, handle: &DocumentHandle) -> Result<DocumentStatus> {
+        loop {
+            let status = self.document_status(handle).await?;
+            if status.is_finished() {
+                return status.check();
+            }
+            tokio::time::sleep(status.poll_interval()).await;
+        }
+    }
+
+    /// Download a translated document.
+    ///
+    /// See [crate::DeepL::download_document].
+    pub async fn download_document(&self, handle: &DocumentHandle) -> Result<Vec<u8>> {
+        let res = self.http_request(
+            Method::POST,
+            &format!("/v2/document/{}/result", handle.document_id),
+            Payload::Params(&[("document_key", handle.document_key.clone())]),
+        ).await?;
+
+        Ok(res.bytes().await?.to_vec())
+    }
+
+    /// Translate a [document](TranslatableDocument).
+    ///
+    /// See [crate::DeepL::translate_document].
+    pub async fn translate_document(
+        &self,
+        options: Option<DocumentTranslationOptions>,
+        document: TranslatableDocument,
+    ) -> Result<TranslatedDocument> {
+        let handle = self.upload_document(options, document).await?;
+        let status = self.wait_for_document(&handle).await?;
+        Ok(TranslatedDocument {
+            billed_characters: status.billed_characters,
+            content: self.download_document(&handle).await?,
+        })
+    }
+}
+
+/// Asynchronous counterpart of [crate::Translator], implemented by [DeepL].
+pub trait Translator {
+    /// See [DeepL::translate].
+    fn translate(
+        &self,
+        options: Option<TranslationOptions>,
+        text_list: TranslatableTextList,
+    ) -> impl Future<Output = Result<Vec<TranslatedText>>> + Send;
+
+    /// See [DeepL::source_languages].
+    fn source_languages(&self) -> impl Future<Output = Result<LanguageList>> + Send;
+
+    /// See [DeepL::target_languages].
+    fn target_languages(&self) -> impl Future<Output = Result<LanguageList>> + Send;
+
+    /// See [DeepL::usage_information].
+    fn usage_information(&self) -> impl Future<Output = Result<UsageInformation>> + Send;
+}
+
+impl Translator for DeepL {
+    fn translate(
+        &self,
+        options: Option<TranslationOptions>,
+        text_list: TranslatableTextList,
+    ) -> impl Future<Output = Result<Vec<TranslatedText>>> + Send {
+        DeepL::translate(self, options, text_list)
+    }
+
+    fn source_languages(&self) -> impl Future<Output = Result<LanguageList>> + Send {
+        DeepL::source_languages(self)
+    }
+
+    fn target_languages(&self) -> impl Future<Output = Result<LanguageList>> + Send {
+        DeepL::target_languages(self)
+    }
+
+    fn usage_information(&self) -> impl Future<Output = Result<UsageInformation>> + Send {
+        DeepL::usage_information(self)
+    }
+}
+
+/// Private helper that runs file system access, e. g. of the [TranslationCache](crate::TranslationCache)
+/// or a [Cassette], on a thread where blocking is allowed, instead of blocking the async executor.
+async fn blocking<T: Send + 'static>(f: impl FnOnce() -> T + Send + 'static) -> T {
+    match tokio::task::spawn_blocking(f).await {
+        Ok(result) => result,
+        Err(e) => std::panic::resume_unwind(e.into_panic()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockServer;
+    use std::sync::OnceLock;
+
+    /// Server shared by all tests of this module.
+    fn mock_server() -> &'static MockServer {
+        static SERVER: OnceLock<MockServer> = OnceLock::new();
+        SERVER.get_or_init(MockServer::start)
+    }
+
+    fn create_deepl() -> DeepL {
+        mock_server().builder().build_async().unwrap()
+    }
+
+    #[tokio::test]
+    async fn usage_information() {
+        let usage_information = create_deepl().usage_information().await.unwrap();
+        assert!(usage_information.character_limit > 0);
+    }
+
+    #[tokio::test]
+    async fn target_languages() {
+        let target_languages = create_deepl().target_languages().await.unwrap();
+        assert_eq!(target_languages.last().unwrap().name, "Chinese (simplified)");
+    }
+
+    #[tokio::test]
+    async fn translate() {
+        let texts = TranslatableTextList {
+            source_language: Some("DE".into()),
+            target_language: "EN-US".into(),
+            texts: vec!["ja".to_string()],
+        };
+        let translated = create_deepl().translate(None, texts).await.unwrap();
+        assert_eq!(translated, vec![TranslatedText {
+            detected_source_language: "DE".to_string(),
+            text: "yes".to_string(),
+            billed_characters: None,
+            model_type_used: None,
+        }]);
+    }
+
+    #[tokio::test]
+    async fn estimate() {
+        let estimate = create_deepl().estimate(&TranslatableTextList {
+            source_language: None,
+            target_language: "DE".into(),
+            texts: vec!["Please go home.".to_string()],
+        }).await.unwrap();
+        assert_eq!(estimate.characters, 15);
+        assert!(estimate.fits_quota());
+    }
+
+    #[tokio::test]
+    async fn translator() {
+        async fn target_language_count(translator: &impl Translator) -> usize {
+            translator.target_languages().await.unwrap().len()
+        }
+        assert!(target_language_count(&create_deepl()).await > 0);
+    }
+
+    #[tokio::test]
+    async fn cassette() {
+        let temp_dir = assert_fs::TempDir::new().unwrap();
+        let path = temp_dir.path().join("cassette.json");
+        let deepl = mock_server().builder().cassette(Cassette::record(&path)).build_async().unwrap();
+        let recorded = deepl.target_languages().await.unwrap();
+
+        let deepl = DeepL::builder("other_key".to_string())
+            .server_url("http://127.0.0.1:1")
+            .cassette(Cassette::replay(&path).unwrap())
+            .build_async()
+            .unwrap();
+        assert_eq!(deepl.target_languages().await.unwrap().len(), recorded.len());
+        assert!(matches!(deepl.target_languages().await.unwrap_err().kind(), ErrorKind::UnmatchedRequest(_)));
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "Error(AuthorizationError")]
+    async fn translate_unauthorized() {
+        let texts = TranslatableTextList {
+            source_language: Some("DE".into()),
+            target_language: "EN-US".into(),
+            texts: vec!["ja".to_string()],
+        };
+        let deepl = crate::DeepL::builder("wrong_key".to_string()).server_url(mock_server().url()).build_async().unwrap();
+        deepl.translate(None, texts).await.unwrap();
+    }
+}
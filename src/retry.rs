@@ -0,0 +1,155 @@
+use crate::{Error, ErrorKind};
+use rand::Rng;
+use reqwest::{header::HeaderMap, StatusCode};
+use std::time::Duration;
+
+/// Controls if and how requests are retried when DeepL is temporarily unable to process them.
+///
+/// Requests are retried on HTTP 429 (too many requests), 503 (service unavailable) and 529
+/// (DeepL-specific "too many requests"). The waiting time between attempts grows exponentially with
+/// some random jitter, unless the server sends a `Retry-After` header, which then takes precedence.
+/// Both are capped at `max_backoff`.
+///
+/// If the request still fails, the returned error is the one of the last attempt, and
+/// [Error::attempts] returns the number of attempts.
+///
+/// # Example
+///
+/// ```rust
+/// use deepl_api::*;
+/// use std::time::Duration;
+///
+/// let deepl = DeepL::builder("YOUR_KEY".to_string())
+///     .retry_policy(RetryPolicy {
+///         max_retries: 3,
+///         ..RetryPolicy::default()
+///     })
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    /// How often a request is retried at most. `0` disables retries.
+    pub max_retries: u32,
+    /// Waiting time before the first retry, it doubles with each further retry.
+    pub initial_backoff: Duration,
+    /// Upper limit for the waiting time between two attempts.
+    pub max_backoff: Duration,
+}
+
+/// Retry up to 5 times, waiting between 1 and 60 seconds.
+impl Default for RetryPolicy {
+    fn default() -> RetryPolicy {
+        RetryPolicy {
+            max_retries: 5,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Never retry. This is used by [DeepL::new](crate::DeepL::new).
+    pub fn none() -> RetryPolicy {
+        RetryPolicy {
+            max_retries: 0,
+            ..RetryPolicy::default()
+        }
+    }
+
+    /// Private method that decides whether another attempt should be made after `attempt` attempts
+    /// failed with `status`.
+    pub(crate) fn should_retry(&self, status: StatusCode, attempt: u32) -> bool {
+        attempt <= self.max_retries
+            && matches!(status.as_u16(), 429 | 503 | 529)
+    }
+
+    /// Private method that calculates the waiting time after `attempt` failed attempts.
+    pub(crate) fn backoff(&self, attempt: u32, headers: &HeaderMap) -> Duration {
+        if let Some(retry_after) = retry_after(headers) {
+            return retry_after.min(self.max_backoff);
+        }
+        let exponential = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_backoff);
+        // Wait between 50% and 100% of the exponential backoff, so that concurrent clients spread out.
+        exponential.mul_f64(rand::thread_rng().gen_range(0.5..=1.0))
+    }
+
+    /// Private method that adds the number of attempts to the error of the last attempt, if there were
+    /// retries. The kind of the error is kept, so that callers can still match on it.
+    pub(crate) fn final_error(&self, error: Error, attempts: u32) -> Error {
+        match attempts {
+            1 => error,
+            _ => Error::with_chain(Error::from(ErrorKind::Attempts(attempts)), error.0),
+        }
+    }
+}
+
+/// Private helper that reads the `Retry-After` header, either in seconds or as an HTTP date.
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(reqwest::header::RETRY_AFTER)?.to_str().ok()?;
+    if let Ok(seconds) = value.trim().parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    // A date in the past means that the request can be retried right away.
+    Some((date.with_timezone(&chrono::Utc) - chrono::Utc::now()).to_std().unwrap_or(Duration::ZERO))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_retry() {
+        let policy = RetryPolicy::default();
+        assert!(policy.should_retry(StatusCode::TOO_MANY_REQUESTS, 1));
+        assert!(policy.should_retry(StatusCode::SERVICE_UNAVAILABLE, 5));
+        assert!(policy.should_retry(StatusCode::from_u16(529).unwrap(), 1));
+        assert!(!policy.should_retry(StatusCode::TOO_MANY_REQUESTS, 6));
+        assert!(!policy.should_retry(StatusCode::INTERNAL_SERVER_ERROR, 1));
+        assert!(!RetryPolicy::none().should_retry(StatusCode::TOO_MANY_REQUESTS, 1));
+    }
+
+    #[test]
+    fn backoff() {
+        let policy = RetryPolicy::default();
+        let headers = HeaderMap::new();
+        for (attempt, max) in [(1, 1), (2, 2), (3, 4), (7, 60), (100, 60)] {
+            let backoff = policy.backoff(attempt, &headers);
+            assert!(backoff >= Duration::from_secs(max) / 2, "attempt {}: {:?}", attempt, backoff);
+            assert!(backoff <= Duration::from_secs(max), "attempt {}: {:?}", attempt, backoff);
+        }
+
+        let mut headers = HeaderMap::new();
+        headers.insert(reqwest::header::RETRY_AFTER, "30".parse().unwrap());
+        assert_eq!(policy.backoff(1, &headers), Duration::from_secs(30));
+        headers.insert(reqwest::header::RETRY_AFTER, "120".parse().unwrap());
+        assert_eq!(policy.backoff(1, &headers), Duration::from_secs(60));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(reqwest::header::RETRY_AFTER, "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap());
+        assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+        assert_eq!(policy.backoff(1, &headers), Duration::ZERO);
+
+        let date = chrono::Utc::now() + chrono::Duration::seconds(30);
+        headers.insert(reqwest::header::RETRY_AFTER, date.to_rfc2822().parse().unwrap());
+        let backoff = policy.backoff(1, &headers);
+        assert!(backoff > Duration::from_secs(28) && backoff <= Duration::from_secs(30), "{:?}", backoff);
+    }
+
+    #[test]
+    fn final_error() {
+        let policy = RetryPolicy::default();
+        let error = policy.final_error(ErrorKind::ServerError("busy".to_string()).into(), 1);
+        assert!(matches!(error.kind(), ErrorKind::ServerError(_)));
+        assert_eq!(error.attempts(), 1);
+
+        let error = policy.final_error(ErrorKind::RateLimited(429, "busy".to_string(), None).into(), 3);
+        assert!(matches!(error.kind(), ErrorKind::RateLimited(429, ..)));
+        assert_eq!(error.attempts(), 3);
+        assert_eq!(error.iter().nth(1).unwrap().to_string(), "The request was sent 3 times.");
+    }
+}
@@ -0,0 +1,198 @@
+use crate::{DeepL, LanguageList, Result, TranslatableTextList, TranslatedText, TranslationOptions, UsageInformation};
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// The text translation functionality of [DeepL] as a trait, so that code depending on it can be
+/// tested with a fake implementation, or the client can be wrapped, e. g. for logging.
+///
+/// The trait is implemented for references and smart pointers as well, so `&dyn Translator` and
+/// `Box<dyn Translator>` can be passed wherever a `Translator` is expected.
+///
+/// # Example
+///
+/// ```rust
+/// use deepl_api::*;
+///
+/// /// Test double that "translates" by reversing the texts.
+/// struct ReverseTranslator;
+///
+/// impl Translator for ReverseTranslator {
+///     fn translate(&self, _: Option<TranslationOptions>, text_list: TranslatableTextList) -> Result<Vec<TranslatedText>> {
+///         Ok(text_list.texts.iter().map(|text| TranslatedText {
+///             detected_source_language: "EN".to_string(),
+///             text: text.chars().rev().collect(),
+///             billed_characters: None,
+///             model_type_used: None,
+///         }).collect())
+///     }
+///     fn source_languages(&self) -> Result<LanguageList> { Ok(vec![]) }
+///     fn target_languages(&self) -> Result<LanguageList> { Ok(vec![]) }
+///     fn usage_information(&self) -> Result<UsageInformation> { Err(ErrorKind::AuthorizationError.into()) }
+/// }
+///
+/// fn greet(translator: &impl Translator) -> String {
+///     let texts = TranslatableTextList {
+///         source_language: None,
+///         target_language: "DE".into(),
+///         texts: vec!["hello".to_string()],
+///     };
+///     translator.translate(None, texts).unwrap().remove(0).text
+/// }
+///
+/// assert_eq!(greet(&ReverseTranslator), "olleh");
+/// ```
+pub trait Translator {
+    /// See [DeepL::translate].
+    fn translate(
+        &self,
+        options: Option<TranslationOptions>,
+        text_list: TranslatableTextList,
+    ) -> Result<Vec<TranslatedText>>;
+
+    /// See [DeepL::source_languages].
+    fn source_languages(&self) -> Result<LanguageList>;
+
+    /// See [DeepL::target_languages].
+    fn target_languages(&self) -> Result<LanguageList>;
+
+    /// See [DeepL::usage_information].
+    fn usage_information(&self) -> Result<UsageInformation>;
+}
+
+impl Translator for DeepL {
+    fn translate(
+        &self,
+        options: Option<TranslationOptions>,
+        text_list: TranslatableTextList,
+    ) -> Result<Vec<TranslatedText>> {
+        DeepL::translate(self, options, text_list)
+    }
+
+    fn source_languages(&self) -> Result<LanguageList> {
+        DeepL::source_languages(self)
+    }
+
+    fn target_languages(&self) -> Result<LanguageList> {
+        DeepL::target_languages(self)
+    }
+
+    fn usage_information(&self) -> Result<UsageInformation> {
+        DeepL::usage_information(self)
+    }
+}
+
+/// Private macro that forwards the trait to the pointee of references and smart pointers.
+macro_rules! forward_translator {
+    ($($pointer:ty),*) => {$(
+        impl<T: Translator + ?Sized> Translator for $pointer {
+            fn translate(
+                &self,
+                options: Option<TranslationOptions>,
+                text_list: TranslatableTextList,
+            ) -> Result<Vec<TranslatedText>> {
+                (**self).translate(options, text_list)
+            }
+
+            fn source_languages(&self) -> Result<LanguageList> {
+                (**self).source_languages()
+            }
+
+            fn target_languages(&self) -> Result<LanguageList> {
+                (**self).target_languages()
+            }
+
+            fn usage_information(&self) -> Result<UsageInformation> {
+                (**self).usage_information()
+            }
+        }
+    )*};
+}
+
+forward_translator!(&T, Box<T>, Rc<T>, Arc<T>);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LanguageInformation;
+    use std::cell::Cell;
+
+    struct FakeTranslator;
+
+    impl Translator for FakeTranslator {
+        fn translate(&self, _: Option<TranslationOptions>, text_list: TranslatableTextList) -> Result<Vec<TranslatedText>> {
+            Ok(text_list.texts.into_iter().map(|text| TranslatedText {
+                detected_source_language: "DE".to_string(),
+                text: text.to_uppercase(),
+                billed_characters: Some(text.len() as u64),
+                model_type_used: None,
+            }).collect())
+        }
+
+        fn source_languages(&self) -> Result<LanguageList> {
+            Ok(vec![LanguageInformation {
+                language: "DE".to_string(),
+                name: "German".to_string(),
+                supports_formality: None,
+            }])
+        }
+
+        fn target_languages(&self) -> Result<LanguageList> {
+            self.source_languages()
+        }
+
+        fn usage_information(&self) -> Result<UsageInformation> {
+            Err(crate::ErrorKind::AuthorizationError.into())
+        }
+    }
+
+    /// Decorator that counts the translated texts.
+    struct CountingTranslator<T> {
+        inner: T,
+        count: Cell<usize>,
+    }
+
+    impl<T: Translator> Translator for CountingTranslator<T> {
+        fn translate(&self, options: Option<TranslationOptions>, text_list: TranslatableTextList) -> Result<Vec<TranslatedText>> {
+            self.count.set(self.count.get() + text_list.texts.len());
+            self.inner.translate(options, text_list)
+        }
+
+        fn source_languages(&self) -> Result<LanguageList> {
+            self.inner.source_languages()
+        }
+
+        fn target_languages(&self) -> Result<LanguageList> {
+            self.inner.target_languages()
+        }
+
+        fn usage_information(&self) -> Result<UsageInformation> {
+            self.inner.usage_information()
+        }
+    }
+
+    fn translate_one(translator: &dyn Translator, text: &str) -> String {
+        let text_list = TranslatableTextList {
+            source_language: None,
+            target_language: "EN-US".into(),
+            texts: vec![text.to_string()],
+        };
+        translator.translate(None, text_list).unwrap().remove(0).text
+    }
+
+    #[test]
+    fn decorator() {
+        let translator = CountingTranslator { inner: Box::new(FakeTranslator), count: Cell::new(0) };
+        assert_eq!(translate_one(&translator, "ja"), "JA");
+        assert_eq!(translate_one(&&translator, "nein"), "NEIN");
+        assert_eq!(translator.count.get(), 2);
+        assert_eq!(translator.target_languages().unwrap()[0].language, "DE");
+        assert!(translator.usage_information().is_err());
+    }
+
+    #[test]
+    fn deepl() {
+        let server = crate::mock::MockServer::start();
+        let translator: Arc<dyn Translator> = Arc::new(server.deepl());
+        assert_eq!(translate_one(&translator, "ja"), "yes");
+    }
+}
@@ -1,6 +1,8 @@
 use assert_cmd::Command;
 use assert_fs::prelude::*;
+use deepl_api::mock::MockServer;
 use predicates::prelude::*;
+use std::io::{BufRead, BufReader};
 
 #[test]
 // Check that help is shown if invoked without any arguments.
@@ -12,13 +14,22 @@ fn test_help() {
         .stderr(predicate::str::contains("Usage"));
 }
 
-// Check success/failure depending on available DEEPL_API_KEY.
+/// The `deepl` command, using the given mock server.
+fn deepl(server: &MockServer) -> Command {
+    let mut cmd = Command::cargo_bin("deepl").unwrap();
+    cmd.env("DEEPL_API_KEY", MockServer::API_KEY)
+        .env("DEEPL_SERVER_URL", server.url());
+    cmd
+}
+
+// Check success/failure depending on the DEEPL_API_KEY.
 #[test]
 fn test_auth() {
-    let mut cmd = Command::cargo_bin("deepl").unwrap();
+    let server = MockServer::start();
+    let mut cmd = deepl(&server);
     cmd.arg("usage-information").assert().success();
 
-    let mut cmd = Command::cargo_bin("deepl").unwrap();
+    let mut cmd = deepl(&server);
     cmd.env("DEEPL_API_KEY", "")
         .arg("usage-information")
         .assert()
@@ -28,7 +39,7 @@ fn test_auth() {
             "Error: no DEEPL_API_KEY found. Please provide your API key in this environment variable.\n",
         ));
 
-    let mut cmd = Command::cargo_bin("deepl").unwrap();
+    let mut cmd = deepl(&server);
     cmd.env("DEEPL_API_KEY", "false")
         .arg("usage-information")
         .assert()
@@ -41,30 +52,47 @@ fn test_auth() {
 
 #[test]
 fn test_usage_information() {
-    let mut cmd = Command::cargo_bin("deepl").unwrap();
+    let server = MockServer::start();
+    let mut cmd = deepl(&server);
     cmd.arg("usage-information")
         .assert()
         .success()
         .stdout(predicate::str::contains(
             "Available characters per billing period:",
         ))
+        .stdout(predicate::str::contains(
+            "Characters remaining in the current billing period:",
+        ))
         .stderr(predicate::eq(""));
 }
 
 #[test]
 fn test_languages() {
-    let mut cmd = Command::cargo_bin("deepl").unwrap();
+    let server = MockServer::start();
+    let mut cmd = deepl(&server);
     cmd.arg("languages")
         .assert()
         .success()
         .stdout(predicate::str::contains("RU    (Russian)"))
+        .stdout(predicate::str::contains("EN-GB           (English (British))"))
+        .stdout(predicate::str::contains("DE    formality (German)"))
+        .stderr(predicate::eq(""));
+
+    let mut cmd = deepl(&server);
+    cmd.arg("languages")
+        .arg("--supports-formality")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("DE    formality (German)"))
+        .stdout(predicate::str::contains("EN-GB").not())
         .stderr(predicate::eq(""));
 }
 
 #[test]
 fn test_translate() {
+    let server = MockServer::start();
     // Missing target language
-    let mut cmd = Command::cargo_bin("deepl").unwrap();
+    let mut cmd = deepl(&server);
     cmd.arg("translate")
         .write_stdin("Please go home.")
         .assert()
@@ -75,7 +103,7 @@ fn test_translate() {
         ));
 
     // STDIN/STDOUT
-    let mut cmd = Command::cargo_bin("deepl").unwrap();
+    let mut cmd = deepl(&server);
     cmd.arg("translate")
         .arg("--source-language")
         .arg("EN")
@@ -87,26 +115,77 @@ fn test_translate() {
         .stdout(predicate::eq("Bitte gehen Sie nach Hause.\n"))
         .stderr(predicate::eq(""));
 
+    // Formality, if supported by the target language
+    let mut cmd = deepl(&server);
+    cmd.arg("translate")
+        .arg("--source-language")
+        .arg("EN")
+        .arg("--target-language")
+        .arg("DE")
+        .arg("--formality-prefer-less")
+        .write_stdin("Please go home.")
+        .assert()
+        .success()
+        .stdout(predicate::eq("Bitte geh nach Hause.\n"))
+        .stderr(predicate::eq(""));
+
+    // Conflicting formality options
+    let mut cmd = deepl(&server);
+    cmd.arg("translate")
+        .arg("--target-language")
+        .arg("DE")
+        .arg("--formality-more")
+        .arg("--formality-prefer-less")
+        .write_stdin("Please go home.")
+        .assert()
+        .code(2)
+        .stdout(predicate::eq(""))
+        .stderr(predicate::str::contains("cannot be used with"));
+
     // Invalid target language
-    let mut cmd = Command::cargo_bin("deepl").unwrap();
+    let mut cmd = deepl(&server);
     cmd.arg("translate")
         .arg("--source-language")
         .arg("EN")
         .arg("--target-language")
-        .arg("FALSE")
+        .arg("XX")
         .write_stdin("Please go home.")
         .assert()
         .code(1)
         .stdout(predicate::eq(""))
         .stderr(predicate::eq("Error: An error occurred while communicating with the DeepL server: \'Value for \'target_lang\' not supported.: \'.\n"));
 
+    // Malformed target language
+    let mut cmd = deepl(&server);
+    cmd.arg("translate")
+        .arg("--source-language")
+        .arg("EN")
+        .arg("--target-language")
+        .arg("EN US")
+        .write_stdin("Please go home.")
+        .assert()
+        .code(1)
+        .stdout(predicate::eq(""))
+        .stderr(predicate::eq("Error: The language code 'EN US' is invalid.\n"));
+
+    // Target language without regional variant
+    let mut cmd = deepl(&server);
+    cmd.arg("translate")
+        .arg("--target-language")
+        .arg("pt")
+        .write_stdin("Please go home.")
+        .assert()
+        .code(1)
+        .stdout(predicate::eq(""))
+        .stderr(predicate::eq("Error: 'PT' needs a regional variant like PT-BR or PT-PT as target language.\n"));
+
     // Via valid files
     let tempdir = assert_fs::TempDir::new().unwrap();
     let input_file = tempdir.child("input.txt");
     input_file.write_str("Please go home.").unwrap();
     let output_file = tempdir.child("output.txt");
 
-    let mut cmd = Command::cargo_bin("deepl").unwrap();
+    let mut cmd = deepl(&server);
     cmd.arg("translate")
         .arg("--source-language")
         .arg("EN")
@@ -124,8 +203,21 @@ fn test_translate() {
 
     output_file.assert("Bitte gehen Sie nach Hause.");
 
+    // Character budget, 45 characters have already been translated
+    let mut cmd = deepl(&server);
+    cmd.arg("translate")
+        .arg("--target-language")
+        .arg("DE")
+        .arg("--max-characters")
+        .arg("50")
+        .write_stdin("Please go home.")
+        .assert()
+        .code(6)
+        .stdout(predicate::eq(""))
+        .stderr(predicate::eq("Error: The translation of 15 characters would exceed the character budget, 5 characters remaining.\n"));
+
     // Invalid input file path.
-    let mut cmd = Command::cargo_bin("deepl").unwrap();
+    let mut cmd = deepl(&server);
     cmd.arg("translate")
         .arg("--source-language")
         .arg("EN")
@@ -144,7 +236,7 @@ fn test_translate() {
         ));
 
     // Invalid output file path.
-    let mut cmd = Command::cargo_bin("deepl").unwrap();
+    let mut cmd = deepl(&server);
     cmd.arg("translate")
         .arg("--source-language")
         .arg("EN")
@@ -162,3 +254,175 @@ fn test_translate() {
             "Error: No such file or directory (os error 2)\n",
         ));
 }
+
+#[test]
+fn test_document() {
+    let server = MockServer::start();
+    // Missing input file
+    let mut cmd = deepl(&server);
+    cmd.arg("document")
+        .arg("--target-language")
+        .arg("DE")
+        .arg("--output-file")
+        .arg("output.txt")
+        .assert()
+        .code(2)
+        .stdout(predicate::eq(""))
+        .stderr(predicate::str::contains(
+            "following required arguments were not provided:",
+        ));
+
+    // Resuming requires both document id and key
+    let mut cmd = deepl(&server);
+    cmd.arg("document")
+        .arg("--document-id")
+        .arg("ID")
+        .arg("--output-file")
+        .arg("output.txt")
+        .assert()
+        .code(2)
+        .stdout(predicate::eq(""))
+        .stderr(predicate::str::contains("--document-key <DOCUMENT_KEY>"));
+
+    // Via valid files
+    let tempdir = assert_fs::TempDir::new().unwrap();
+    let input_file = tempdir.child("input.txt");
+    input_file.write_str("Please go home.").unwrap();
+    let output_file = tempdir.child("output.txt");
+
+    let mut cmd = deepl(&server);
+    cmd.arg("document")
+        .arg("--source-language")
+        .arg("EN")
+        .arg("--target-language")
+        .arg("DE")
+        .arg("--formality-more")
+        .arg("--input-file")
+        .arg(input_file.path())
+        .arg("--output-file")
+        .arg(output_file.path())
+        .assert()
+        .success()
+        .stdout(predicate::eq(""))
+        .stderr(predicate::str::contains("--document-id"))
+        .stderr(predicate::str::contains("Waiting for translation to start..."))
+        .stderr(predicate::str::contains("Translating, about 1 seconds remaining..."))
+        .stderr(predicate::str::contains("characters billed."));
+
+    output_file.assert("Bitte gehen Sie nach Hause.");
+
+    // Resume an interrupted translation into another file
+    let mut child = std::process::Command::new(assert_cmd::cargo::cargo_bin("deepl"))
+        .env("DEEPL_API_KEY", MockServer::API_KEY)
+        .env("DEEPL_SERVER_URL", server.url())
+        .arg("document")
+        .arg("--target-language")
+        .arg("DE")
+        .arg("--input-file")
+        .arg(input_file.path())
+        .arg("--output-file")
+        .arg(output_file.path())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut uploaded = String::new();
+    BufReader::new(child.stderr.take().unwrap())
+        .read_line(&mut uploaded)
+        .unwrap();
+    child.kill().unwrap();
+    child.wait().unwrap();
+    let resume: Vec<&str> = uploaded
+        .split_once("resume with: ")
+        .unwrap()
+        .1
+        .split_whitespace()
+        .collect();
+    assert_eq!(resume.len(), 4);
+
+    let resumed_file = tempdir.child("resumed.txt");
+    let mut cmd = deepl(&server);
+    cmd.arg("document")
+        .args(&resume)
+        .arg("--output-file")
+        .arg(resumed_file.path())
+        .assert()
+        .success()
+        .stdout(predicate::eq(""))
+        .stderr(predicate::str::contains("--document-id").not());
+
+    resumed_file.assert("Bitte gehen Sie nach Hause.");
+}
+
+#[test]
+fn test_estimate() {
+    let server = MockServer::start();
+    let mut cmd = deepl(&server);
+    cmd.args(["translate", "--target-language", "DE", "--dry-run"])
+        .write_stdin("Please go home.")
+        .assert()
+        .success()
+        .stdout(predicate::eq(
+            "Billable characters: 15\nCharacters remaining in the current billing period: 500000\nCharacters remaining after the translation: 499985\n",
+        ))
+        .stderr(predicate::eq(""));
+    assert_eq!(server.character_count(), 0);
+
+    let tempdir = assert_fs::TempDir::new().unwrap();
+    let input_file = tempdir.child("input.txt");
+    input_file.write_str("Please go home.").unwrap();
+    let other_input_file = tempdir.child("other_input.txt");
+    other_input_file.write_str("ja").unwrap();
+
+    let mut cmd = deepl(&server);
+    cmd.args(["estimate", "--target-language", "DE,FR"])
+        .arg(input_file.path())
+        .arg(other_input_file.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("Billable characters: 34\n"))
+        .stderr(predicate::eq(""));
+
+    // Missing target language
+    let mut cmd = deepl(&server);
+    cmd.arg("estimate")
+        .arg(input_file.path())
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("--target-language"));
+}
+
+#[test]
+fn test_cache() {
+    let server = MockServer::start();
+    let tempdir = assert_fs::TempDir::new().unwrap();
+    let cache_dir = tempdir.path().to_str().unwrap();
+
+    // Managing the cache works without API key
+    let mut cmd = deepl(&server);
+    cmd.env_remove("DEEPL_API_KEY")
+        .args(["cache", "stats", "--cache-dir", cache_dir])
+        .assert()
+        .success()
+        .stdout(predicate::eq("Cached translations: 0\nCache size in bytes: 0\n"));
+
+    // Repeated translations are served from the cache
+    for _ in 0..2 {
+        let mut cmd = deepl(&server);
+        cmd.args(["translate", "--source-language", "EN", "--target-language", "DE", "--cache-dir", cache_dir])
+            .write_stdin("Please go home.")
+            .assert()
+            .success()
+            .stdout(predicate::eq("Bitte gehen Sie nach Hause.\n"));
+    }
+    let mut cmd = deepl(&server);
+    cmd.args(["cache", "stats", "--cache-dir", cache_dir])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("Cached translations: 1\n"));
+
+    let mut cmd = deepl(&server);
+    cmd.args(["cache", "clear", "--cache-dir", cache_dir])
+        .assert()
+        .success()
+        .stdout(predicate::eq("Removed 1 cached translations.\n"));
+}